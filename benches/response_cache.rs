@@ -0,0 +1,102 @@
+//! Compare the throughput of `LruResponseCache`, which is guarded by one `Mutex`, with `ShardedResponseCache` as the number of threads grows.
+//!
+//! ```bash
+//! cargo bench --bench response_cache
+//! ```
+
+use std::{
+    sync::{Arc, Barrier},
+    thread,
+    time::{Duration, Instant},
+};
+
+use rocket_include_handlebars::*;
+
+const CAPACITY: usize = 1024;
+const OPERATIONS_PER_THREAD: usize = 200_000;
+
+fn entry(html: &str) -> CacheEntry {
+    CacheEntry::new(Arc::from(html), Arc::new(EntityTag::from_data(html)))
+}
+
+/// Run `OPERATIONS_PER_THREAD` operations in each thread, where one in ten is an insertion and the rest are lookups, and return how long it takes.
+fn run(cache: Arc<dyn ResponseCache>, threads: usize) -> Duration {
+    let keys: Arc<[String]> = (0..CAPACITY * 2).map(|i| format!("page/{}", i)).collect();
+
+    for key in keys.iter().take(CAPACITY) {
+        cache.insert(key.clone(), entry(key));
+    }
+
+    let barrier = Arc::new(Barrier::new(threads + 1));
+
+    let handles = (0..threads)
+        .map(|t| {
+            let cache = cache.clone();
+            let keys = keys.clone();
+            let barrier = barrier.clone();
+
+            thread::spawn(move || {
+                // xorshift, so that the threads do not walk the keys in the same order
+                let mut state = 0x9E37_79B9_7F4A_7C15u64 ^ (t as u64 + 1);
+
+                barrier.wait();
+
+                for i in 0..OPERATIONS_PER_THREAD {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+
+                    let key = &keys[(state % keys.len() as u64) as usize];
+
+                    if i % 10 == 0 {
+                        cache.insert(key.clone(), entry(key));
+                    } else {
+                        cache.get(key);
+                    }
+                }
+
+                barrier.wait();
+            })
+        })
+        .collect::<Vec<_>>();
+
+    barrier.wait();
+
+    let start = Instant::now();
+
+    barrier.wait();
+
+    let elapsed = start.elapsed();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    elapsed
+}
+
+fn main() {
+    let parallelism = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+    let mut thread_counts = vec![1];
+
+    while thread_counts[thread_counts.len() - 1] < parallelism {
+        thread_counts.push((thread_counts[thread_counts.len() - 1] * 2).min(parallelism));
+    }
+
+    println!("{:>8} {:>16} {:>16}", "threads", "lru (Mops/s)", "sharded (Mops/s)");
+
+    for threads in thread_counts {
+        let operations = (threads * OPERATIONS_PER_THREAD) as f64;
+
+        let lru = run(Arc::new(LruResponseCache::new(CAPACITY)), threads);
+        let sharded = run(Arc::new(ShardedResponseCache::new(CAPACITY)), threads);
+
+        println!(
+            "{:>8} {:>16.2} {:>16.2}",
+            threads,
+            operations / lru.as_secs_f64() / 1e6,
+            operations / sharded.as_secs_f64() / 1e6
+        );
+    }
+}
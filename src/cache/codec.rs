@@ -0,0 +1,136 @@
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{CacheEntry, TemplateVersion};
+use crate::EntityTag;
+
+/// The version of the layout. Entries encoded by an older layout cannot be decoded.
+const FORMAT: u8 = 2;
+
+// The layout of an encoded entry is
+// [FORMAT (u8)][`created_at`][0 (u8) or 1 (u8) followed by `expires_at`][length of ETag (u32)][ETag]
+// [number of templates (u32)]([length of a name (u32)][name][length of a hash (u32)][hash])*[HTML],
+// where a time is [seconds since the Unix epoch (u64)][nanoseconds (u32)], and the integers are big-endian.
+
+#[inline]
+fn write_bytes(value: &mut Vec<u8>, bytes: &[u8]) {
+    value.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    value.extend_from_slice(bytes);
+}
+
+#[inline]
+fn write_time(value: &mut Vec<u8>, time: SystemTime) {
+    let time = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+    value.extend_from_slice(&time.as_secs().to_be_bytes());
+    value.extend_from_slice(&time.subsec_nanos().to_be_bytes());
+}
+
+#[inline]
+fn read_u32(value: &mut &[u8]) -> Option<u32> {
+    let n = u32::from_be_bytes(value.get(..4)?.try_into().ok()?);
+
+    *value = &value[4..];
+
+    Some(n)
+}
+
+#[inline]
+fn read_time(value: &mut &[u8]) -> Option<SystemTime> {
+    let secs = u64::from_be_bytes(value.get(..8)?.try_into().ok()?);
+
+    *value = &value[8..];
+
+    let nanos = read_u32(value)?;
+
+    UNIX_EPOCH.checked_add(Duration::new(secs, nanos))
+}
+
+#[inline]
+fn read_str<'a>(value: &mut &'a [u8]) -> Option<&'a str> {
+    let len = read_u32(value)? as usize;
+
+    let s = std::str::from_utf8(value.get(..len)?).ok()?;
+
+    *value = &value[len..];
+
+    Some(s)
+}
+
+pub(crate) fn encode_entry(entry: &CacheEntry) -> Vec<u8> {
+    let etag = entry.etag.to_string();
+
+    let mut value = Vec::with_capacity(34 + etag.len() + entry.html.len());
+
+    value.push(FORMAT);
+    write_time(&mut value, entry.created_at);
+
+    match entry.expires_at {
+        Some(expires_at) => {
+            value.push(1);
+            write_time(&mut value, expires_at);
+        },
+        None => value.push(0),
+    }
+
+    write_bytes(&mut value, etag.as_bytes());
+
+    value.extend_from_slice(&(entry.templates.len() as u32).to_be_bytes());
+
+    for template in entry.templates.iter() {
+        write_bytes(&mut value, template.name.as_bytes());
+        write_bytes(&mut value, template.hash.as_bytes());
+    }
+
+    value.extend_from_slice(entry.html.as_bytes());
+
+    value
+}
+
+pub(crate) fn decode_entry(value: &[u8]) -> Option<CacheEntry> {
+    let mut value = value.strip_prefix(&[FORMAT])?;
+
+    let created_at = read_time(&mut value)?;
+
+    let expires_at = match *value.first()? {
+        0 => {
+            value = &value[1..];
+
+            None
+        },
+        1 => {
+            value = &value[1..];
+
+            Some(read_time(&mut value)?)
+        },
+        _ => return None,
+    };
+
+    let etag = EntityTag::from_string(read_str(&mut value)?).ok()?;
+
+    let template_count = read_u32(&mut value)? as usize;
+
+    let mut templates = Vec::with_capacity(template_count.min(16));
+
+    for _ in 0..template_count {
+        let name = String::from(read_str(&mut value)?);
+        let hash = String::from(read_str(&mut value)?);
+
+        templates.push(TemplateVersion {
+            name,
+            hash,
+        });
+    }
+
+    let html = std::str::from_utf8(value).ok()?;
+
+    Some(CacheEntry {
+        html: Arc::from(html),
+        etag: Arc::new(etag.into_owned()),
+        created_at,
+        expires_at,
+        templates: Arc::from(templates),
+    })
+}
@@ -0,0 +1,122 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    RwLock,
+};
+
+use super::CacheStats;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Counter {
+    Hits,
+    Misses,
+    Insertions,
+    Evictions,
+    NotModified,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    counts: [AtomicU64; 5],
+}
+
+impl Counters {
+    #[inline]
+    fn add(&self, counter: Counter) {
+        self.counts[counter as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn get(&self, counter: Counter) -> u64 {
+        self.counts[counter as usize].load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    fn reset(&self) {
+        for count in self.counts.iter() {
+            count.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The counters of the whole cache and of the tracked key prefixes.
+#[derive(Debug, Default)]
+pub(crate) struct StatsCounters {
+    counters:        Counters,
+    prefix_counters: RwLock<Vec<(String, Counters)>>,
+}
+
+impl StatsCounters {
+    #[inline]
+    pub(crate) fn record(&self, key: &str, counter: Counter) {
+        self.counters.add(counter);
+
+        for (prefix, counters) in self.prefix_counters.read().unwrap().iter() {
+            if key.starts_with(prefix.as_str()) {
+                counters.add(counter);
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn track_prefix(&self, prefix: &str) {
+        let mut prefix_counters = self.prefix_counters.write().unwrap();
+
+        if !prefix_counters.iter().any(|(p, _)| p == prefix) {
+            prefix_counters.push((String::from(prefix), Counters::default()));
+        }
+    }
+
+    /// Add the counters of the whole cache, or of a tracked key prefix, to `stats`. Return `false` if the prefix is not tracked.
+    pub(crate) fn add_to(&self, prefix: Option<&str>, stats: &mut CacheStats) -> bool {
+        let prefix_counters = self.prefix_counters.read().unwrap();
+
+        let counters = match prefix {
+            Some(prefix) => match prefix_counters.iter().find(|(p, _)| p == prefix) {
+                Some((_, counters)) => counters,
+                None => return false,
+            },
+            None => &self.counters,
+        };
+
+        stats.hits += counters.get(Counter::Hits);
+        stats.misses += counters.get(Counter::Misses);
+        stats.insertions += counters.get(Counter::Insertions);
+        stats.evictions += counters.get(Counter::Evictions);
+        stats.not_modified += counters.get(Counter::NotModified);
+
+        true
+    }
+
+    /// `sizes` are the keys and the HTML lengths of all caches currently held.
+    pub(crate) fn snapshot<'a, I: Iterator<Item = (&'a str, usize)>>(
+        &self,
+        prefix: Option<&str>,
+        sizes: I,
+    ) -> Option<CacheStats> {
+        let mut stats = CacheStats::default();
+
+        if !self.add_to(prefix, &mut stats) {
+            return None;
+        }
+
+        let prefix = prefix.unwrap_or("");
+
+        for (key, size) in sizes {
+            if key.starts_with(prefix) {
+                stats.entries += 1;
+                stats.bytes += size;
+            }
+        }
+
+        Some(stats)
+    }
+
+    #[inline]
+    pub(crate) fn reset(&self) {
+        self.counters.reset();
+
+        for (_, counters) in self.prefix_counters.read().unwrap().iter() {
+            counters.reset();
+        }
+    }
+}
@@ -0,0 +1,177 @@
+#[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+    Renderable,
+};
+#[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+use handlebars::{JsonRender, StringOutput};
+
+#[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+use super::{CacheEntry, ResponseCache, TemplateVersions};
+#[cfg(all(feature = "i18n", any(not(debug_assertions), feature = "debug_cache")))]
+use crate::Translations;
+#[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+use crate::{functions::compute_data_etag, Locale};
+
+/// The prefix of the cache keys of fragments.
+pub const FRAGMENT_KEY_PREFIX: &str = "fragment:";
+
+/// The `cache` block helper, which caches the rendered block in the cache table of `HandlebarsContextManager`.
+///
+/// `{{#cache "sidebar" ttl=300}}...{{/cache}}` caches the block by the key `fragment:sidebar` for 300 seconds. `ttl` is a number of seconds, or a string of it. Parameters after the name are appended to the key, separated by `:`, so `{{#cache "menu" user.id}}` keeps one cache per user. The characters `\`, `:` and `@` in the name and the parameters are escaped with a `\`, so that different parameters cannot make the same key. In a render by `build_localized`, the language tag of the resolved variant of the template, and the locale negotiated by the translations of the `i18n` feature, are appended as well, like `fragment:sidebar@de`. Without `ttl`, the cache is kept until it is evicted or the template is changed.
+#[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+pub(crate) struct FragmentCacheHelper {
+    pub(crate) cache_table:       Arc<dyn ResponseCache>,
+    pub(crate) template_versions: Arc<RwLock<TemplateVersions>>,
+    #[cfg(feature = "i18n")]
+    pub(crate) translations:      Option<Arc<Translations>>,
+}
+
+/// The `cache` block helper. The cache is generated only when you are using the **release** profile, unless the `debug_cache` feature is enabled, so this helper just renders the block.
+#[cfg(all(debug_assertions, not(feature = "debug_cache")))]
+pub(crate) struct FragmentCacheHelper;
+
+#[inline]
+fn fragment_name<'a>(h: &'a Helper) -> Result<&'a str, RenderError> {
+    h.param(0)
+        .and_then(|param| param.value().as_str())
+        .ok_or_else(|| RenderError::new("The `cache` helper needs a name as its first parameter."))
+}
+
+#[inline]
+fn fragment_ttl(h: &Helper) -> Result<Option<Duration>, RenderError> {
+    let ttl = match h.hash_get("ttl") {
+        Some(ttl) => ttl.value(),
+        None => return Ok(None),
+    };
+
+    ttl.as_u64()
+        .or_else(|| ttl.as_str().and_then(|ttl| ttl.trim().parse().ok()))
+        .map(|ttl| Some(Duration::from_secs(ttl)))
+        .ok_or_else(|| {
+            RenderError::new(
+                "The `ttl` of the `cache` helper needs to be a non-negative integer of seconds.",
+            )
+        })
+}
+
+/// Append a component to the key of a fragment, with the separators in it escaped.
+#[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+#[inline]
+fn push_key_component(key: &mut String, component: &str) {
+    for c in component.chars() {
+        if matches!(c, '\\' | ':' | '@') {
+            key.push('\\');
+        }
+
+        key.push(c);
+    }
+}
+
+#[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+impl HelperDef for FragmentCacheHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let name = fragment_name(h)?;
+        let ttl = fragment_ttl(h)?;
+
+        let template = match h.template() {
+            Some(template) => template,
+            None => return Ok(()),
+        };
+
+        let mut key = String::from(FRAGMENT_KEY_PREFIX);
+
+        push_key_component(&mut key, name);
+
+        for param in h.params().iter().skip(1) {
+            key.push(':');
+            push_key_component(&mut key, &param.value().render());
+        }
+
+        let suffix = match (Locale::current(), rc.get_root_template_name()) {
+            #[cfg(feature = "i18n")]
+            (Some(locale), Some(template)) => {
+                locale.cache_key_suffix(template, self.translations.as_deref())
+            },
+            #[cfg(not(feature = "i18n"))]
+            (Some(locale), Some(template)) => locale.cache_key_suffix(template),
+            _ => None,
+        };
+
+        if let Some(suffix) = suffix {
+            key.push('@');
+            key.push_str(&suffix);
+        }
+
+        if let Some(entry) = self.cache_table.get(&key) {
+            let fresh = !entry.is_expired()
+                && self
+                    .template_versions
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .is_current(&entry.templates);
+
+            if fresh {
+                return out.write(&entry.html).map_err(RenderError::from);
+            }
+        }
+
+        let mut buffer = StringOutput::new();
+
+        template.render(r, ctx, rc, &mut buffer)?;
+
+        let html = buffer.into_string().map_err(RenderError::from)?;
+
+        out.write(&html)?;
+
+        let templates = rc
+            .get_current_template_name()
+            .and_then(|name| {
+                self.template_versions.read().unwrap_or_else(PoisonError::into_inner).get(name)
+            })
+            .unwrap_or_else(|| Arc::new([]));
+
+        let etag = compute_data_etag(&html);
+
+        let mut entry = CacheEntry::new(Arc::from(html), Arc::new(etag)).with_templates(templates);
+
+        if let Some(ttl) = ttl {
+            entry = entry.with_ttl(ttl);
+        }
+
+        self.cache_table.insert(key, entry);
+
+        Ok(())
+    }
+}
+
+#[cfg(all(debug_assertions, not(feature = "debug_cache")))]
+impl HelperDef for FragmentCacheHelper {
+    #[inline]
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        fragment_name(h)?;
+        fragment_ttl(h)?;
+
+        match h.template() {
+            Some(template) => template.render(r, ctx, rc, out),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::{borrow::Cow, fmt::Write};
+
+use rocket::{
+    http::{uri::Origin, CookieJar, HeaderMap, RawStr},
+    request::{FromRequest, Outcome, Request},
+};
+
+use crate::HandlebarsResponse;
+
+/// A request guard which derives cache keys from the path of the request and the query parameters, headers and cookies you select, so that requests which can get different responses do not share a cache.
+///
+/// ```rust,ignore
+/// #[get("/list")]
+/// fn list(
+///     cm: &State<HandlebarsContextManager>,
+///     etag_if_none_match: EtagIfNoneMatch,
+///     key: CacheKey<'_>,
+/// ) -> HandlebarsResponse {
+///     let key = key.query("page").header("Accept-Language");
+///
+///     handlebars_response_cache!(cm, etag_if_none_match, key, {
+///         handlebars_response!(cm, EtagIfNoneMatch::default(), "list")
+///     })
+/// }
+/// ```
+///
+/// When a `CacheKey` is given to `handlebars_response_cache!`, the **Vary** header generated by `vary_header` is added to the response, whether it is served from the cache or not. Query parameters are percent-decoded, and the selected query parameters, headers and cookies are sorted by their names, so requests which only differ in the encoding or the order of their parameters share one cache.
+#[derive(Debug, Clone)]
+pub struct CacheKey<'r> {
+    uri:          &'r Origin<'r>,
+    headers:      &'r HeaderMap<'r>,
+    jar:          &'r CookieJar<'r>,
+    query_names:  Vec<String>,
+    header_names: Vec<String>,
+    cookie_names: Vec<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CacheKey<'r> {
+    type Error = ();
+
+    #[inline]
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(CacheKey {
+            uri:          request.uri(),
+            headers:      request.headers(),
+            jar:          request.cookies(),
+            query_names:  Vec::new(),
+            header_names: Vec::new(),
+            cookie_names: Vec::new(),
+        })
+    }
+}
+
+impl<'r> CacheKey<'r> {
+    /// Make the cache key depend on a query parameter.
+    #[inline]
+    pub fn query<S: Into<String>>(mut self, name: S) -> Self {
+        self.query_names.push(name.into());
+
+        self
+    }
+
+    /// Make the cache key depend on a request header. The header is also listed in the **Vary** header.
+    #[inline]
+    pub fn header<S: Into<String>>(mut self, name: S) -> Self {
+        self.header_names.push(name.into());
+
+        self
+    }
+
+    /// Make the cache key depend on a cookie. **Cookie** is also listed in the **Vary** header.
+    #[inline]
+    pub fn cookie<S: Into<String>>(mut self, name: S) -> Self {
+        self.cookie_names.push(name.into());
+
+        self
+    }
+
+    /// Generate the cache key.
+    pub fn to_key(&self) -> String {
+        let uri = self.uri;
+
+        let mut key = String::from(uri.path().as_str());
+
+        // query values are percent-encoded again after they are decoded, and header values and cookie values cannot contain line breaks, so every part is put in its own line
+        for name in sorted_names(&self.query_names, false) {
+            let values = uri.query().into_iter().flat_map(|query| query.raw_segments()).filter_map(
+                |segment| {
+                    let (n, v) = segment.as_str().split_once('=').unwrap_or((segment.as_str(), ""));
+
+                    if RawStr::new(n).url_decode_lossy() == name {
+                        Some(RawStr::new(v).url_decode_lossy())
+                    } else {
+                        None
+                    }
+                },
+            );
+
+            // the order of the values of a parameter is kept, because it can matter
+            for value in values {
+                write!(
+                    key,
+                    "\nq:{}={}",
+                    RawStr::new(name).percent_encode(),
+                    RawStr::new(&value).percent_encode()
+                )
+                .unwrap();
+            }
+        }
+
+        for name in sorted_names(&self.header_names, true) {
+            for value in self.headers.get(name) {
+                write!(key, "\nh:{}={}", name.to_ascii_lowercase(), value).unwrap();
+            }
+        }
+
+        for name in sorted_names(&self.cookie_names, false) {
+            if let Some(cookie) = self.jar.get(name) {
+                write!(key, "\nc:{}={}", name, cookie.value()).unwrap();
+            }
+        }
+
+        key
+    }
+
+    /// Generate the value of the **Vary** header, which lists the selected headers, and **Cookie** if any cookie is selected.
+    pub fn vary_header(&self) -> Option<String> {
+        let mut names: Vec<&str> = self.header_names.iter().map(String::as_str).collect();
+
+        if !self.cookie_names.is_empty() {
+            names.push("Cookie");
+        }
+
+        let names = sorted_names(&names, true);
+
+        if names.is_empty() {
+            None
+        } else {
+            Some(names.join(", "))
+        }
+    }
+
+    /// Add the **Vary** header generated by `vary_header` to a `HandlebarsResponse`.
+    #[inline]
+    pub fn vary(&self, res: HandlebarsResponse) -> HandlebarsResponse {
+        match self.vary_header() {
+            Some(vary) => res.vary(vary),
+            None => res,
+        }
+    }
+}
+
+/// Sort names and remove duplicated ones.
+fn sorted_names<S: AsRef<str>>(names: &[S], ignore_case: bool) -> Vec<&str> {
+    let mut names: Vec<&str> = names.iter().map(AsRef::as_ref).collect();
+
+    if ignore_case {
+        names.sort_unstable_by_key(|name| name.to_ascii_lowercase());
+        names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    } else {
+        names.sort_unstable();
+        names.dedup();
+    }
+
+    names
+}
+
+/// A key of the response cache, which `handlebars_response_cache!` accepts. It is implemented for strings and `CacheKey`.
+pub trait AsCacheKey {
+    /// Get the key of the cache.
+    fn as_cache_key(&self) -> Cow<'_, str>;
+
+    /// Prepare a response which is cached or served with this key, such as adding headers to it.
+    #[inline]
+    fn prepare_response(&self, res: HandlebarsResponse) -> HandlebarsResponse {
+        res
+    }
+}
+
+impl<T: AsRef<str> + ?Sized> AsCacheKey for T {
+    #[inline]
+    fn as_cache_key(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.as_ref())
+    }
+}
+
+impl AsCacheKey for CacheKey<'_> {
+    #[inline]
+    fn as_cache_key(&self) -> Cow<'_, str> {
+        Cow::Owned(self.to_key())
+    }
+
+    /// Add the **Vary** header.
+    #[inline]
+    fn prepare_response(&self, res: HandlebarsResponse) -> HandlebarsResponse {
+        self.vary(res)
+    }
+}
@@ -0,0 +1,244 @@
+use std::{
+    collections::BTreeMap,
+    ops::Bound,
+    sync::{Arc, Mutex},
+};
+
+use super::{
+    decode_entry, encode_entry, CacheEntry, CacheEntryInfo, CacheStats, Counter, ResponseCache,
+    StatsCounters,
+};
+
+/// An external key-value store, such as Redis or Memcached, which can be shared by several Rocket instances. Implementations should treat a failed operation as a missing value.
+pub trait KeyValueStore: Send + Sync {
+    /// Get the value of a key.
+    fn get(&self, key: &str) -> Option<Vec<u8>>;
+
+    /// Set the value of a key.
+    fn set(&self, key: &str, value: Vec<u8>);
+
+    /// Delete a key. Return whether the key existed.
+    fn delete(&self, key: &str) -> bool;
+
+    /// Get all keys which start with a specific prefix.
+    fn keys(&self, prefix: &str) -> Vec<String>;
+}
+
+impl<T: KeyValueStore + ?Sized> KeyValueStore for Arc<T> {
+    #[inline]
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        (**self).get(key)
+    }
+
+    #[inline]
+    fn set(&self, key: &str, value: Vec<u8>) {
+        (**self).set(key, value)
+    }
+
+    #[inline]
+    fn delete(&self, key: &str) -> bool {
+        (**self).delete(key)
+    }
+
+    #[inline]
+    fn keys(&self, prefix: &str) -> Vec<String> {
+        (**self).keys(prefix)
+    }
+}
+
+/// A `KeyValueStore` in local memory, which can stand in for an external store in tests.
+#[derive(Debug, Default)]
+pub struct MemoryKeyValueStore {
+    map: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemoryKeyValueStore {
+    /// Create an empty `MemoryKeyValueStore`.
+    #[inline]
+    pub fn new() -> MemoryKeyValueStore {
+        MemoryKeyValueStore::default()
+    }
+}
+
+impl KeyValueStore for MemoryKeyValueStore {
+    #[inline]
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.map.lock().unwrap().get(key).cloned()
+    }
+
+    #[inline]
+    fn set(&self, key: &str, value: Vec<u8>) {
+        self.map.lock().unwrap().insert(String::from(key), value);
+    }
+
+    #[inline]
+    fn delete(&self, key: &str) -> bool {
+        self.map.lock().unwrap().remove(key).is_some()
+    }
+
+    #[inline]
+    fn keys(&self, prefix: &str) -> Vec<String> {
+        self.map
+            .lock()
+            .unwrap()
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+}
+
+const DEFAULT_NAMESPACE: &str = "rocket-include-handlebars:";
+
+/// A `ResponseCache` which stores caches in a `KeyValueStore`. The capacity and the expiration of caches are managed by the store, so no eviction is recorded.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct KeyValueResponseCache<S: KeyValueStore> {
+    #[educe(Debug(ignore))]
+    store:     S,
+    namespace: String,
+    counters:  StatsCounters,
+}
+
+impl<S: KeyValueStore> KeyValueResponseCache<S> {
+    /// Create an instance of `KeyValueResponseCache` whose keys are prefixed with `rocket-include-handlebars:` in the store.
+    #[inline]
+    pub fn new(store: S) -> KeyValueResponseCache<S> {
+        KeyValueResponseCache::with_namespace(store, DEFAULT_NAMESPACE)
+    }
+
+    /// Create an instance of `KeyValueResponseCache` whose keys are prefixed with `namespace` in the store.
+    #[inline]
+    pub fn with_namespace<N: Into<String>>(store: S, namespace: N) -> KeyValueResponseCache<S> {
+        KeyValueResponseCache {
+            store,
+            namespace: namespace.into(),
+            counters: StatsCounters::default(),
+        }
+    }
+
+    #[inline]
+    fn store_key(&self, key: &str) -> String {
+        format!("{}{}", self.namespace, key)
+    }
+
+    #[inline]
+    fn fetch(&self, store_key: &str) -> Option<CacheEntry> {
+        self.store.get(store_key).and_then(|value| decode_entry(&value))
+    }
+
+    fn sizes(&self) -> Vec<(String, usize)> {
+        self.store
+            .keys(self.namespace.as_str())
+            .into_iter()
+            .filter_map(|store_key| {
+                self.fetch(store_key.as_str()).map(|entry| {
+                    (String::from(&store_key[self.namespace.len()..]), entry.html.len())
+                })
+            })
+            .collect()
+    }
+}
+
+impl<S: KeyValueStore> ResponseCache for KeyValueResponseCache<S> {
+    #[inline]
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        let entry = self.fetch(self.store_key(key).as_str());
+
+        self.counters.record(key, if entry.is_some() { Counter::Hits } else { Counter::Misses });
+
+        entry
+    }
+
+    #[inline]
+    fn peek(&self, key: &str) -> Option<CacheEntry> {
+        self.fetch(self.store_key(key).as_str())
+    }
+
+    #[inline]
+    fn contains_key(&self, key: &str) -> bool {
+        self.fetch(self.store_key(key).as_str()).is_some()
+    }
+
+    #[inline]
+    fn insert(&self, key: String, entry: CacheEntry) -> Option<CacheEntry> {
+        let store_key = self.store_key(key.as_str());
+
+        let old_entry = self.fetch(store_key.as_str());
+
+        self.store.set(store_key.as_str(), encode_entry(&entry));
+
+        self.counters.record(key.as_str(), Counter::Insertions);
+
+        old_entry
+    }
+
+    #[inline]
+    fn remove(&self, key: &str) -> Option<CacheEntry> {
+        let store_key = self.store_key(key);
+
+        let entry = self.fetch(store_key.as_str());
+
+        self.store.delete(store_key.as_str());
+
+        entry
+    }
+
+    #[inline]
+    fn remove_prefix(&self, prefix: &str) -> usize {
+        self.store
+            .keys(self.store_key(prefix).as_str())
+            .into_iter()
+            .filter(|store_key| self.store.delete(store_key.as_str()))
+            .count()
+    }
+
+    #[inline]
+    fn clear(&self) {
+        self.remove_prefix("");
+    }
+
+    fn entries(&self) -> Vec<CacheEntryInfo> {
+        self.store
+            .keys(self.namespace.as_str())
+            .into_iter()
+            .filter_map(|store_key| {
+                self.fetch(store_key.as_str()).map(|entry| CacheEntryInfo {
+                    key:  String::from(&store_key[self.namespace.len()..]),
+                    size: entry.html.len(),
+                    age:  entry.age(),
+                })
+            })
+            .collect()
+    }
+
+    #[inline]
+    fn stats(&self) -> CacheStats {
+        let sizes = self.sizes();
+
+        self.counters.snapshot(None, sizes.iter().map(|(key, size)| (key.as_str(), *size))).unwrap()
+    }
+
+    #[inline]
+    fn record_not_modified(&self, key: &str) {
+        self.counters.record(key, Counter::NotModified);
+    }
+
+    #[inline]
+    fn track_stats_prefix(&self, prefix: &str) {
+        self.counters.track_prefix(prefix);
+    }
+
+    #[inline]
+    fn stats_prefix(&self, prefix: &str) -> Option<CacheStats> {
+        let sizes = self.sizes();
+
+        self.counters.snapshot(Some(prefix), sizes.iter().map(|(key, size)| (key.as_str(), *size)))
+    }
+
+    #[inline]
+    fn reset_stats(&self) {
+        self.counters.reset();
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::{Mutex, MutexGuard};
+
+use lru_time_cache::LruCache;
+
+use super::{CacheEntry, CacheEntryInfo, CacheStats, Counter, ResponseCache, StatsCounters};
+
+/// A `ResponseCache` which keeps caches in memory and evicts the exact least recently used one when it is full. All operations share a single lock, so `ShardedResponseCache` scales better under high concurrency.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct LruResponseCache {
+    capacity: usize,
+    #[educe(Debug(ignore))]
+    lru:      Mutex<LruCache<String, CacheEntry>>,
+    counters: StatsCounters,
+}
+
+impl LruResponseCache {
+    /// Create an instance of `LruResponseCache` which can hold at most `capacity` caches.
+    #[inline]
+    pub fn new(capacity: usize) -> LruResponseCache {
+        LruResponseCache {
+            capacity,
+            lru: Mutex::new(LruCache::with_capacity(capacity)),
+            counters: StatsCounters::default(),
+        }
+    }
+
+    #[inline]
+    fn lru(&self) -> MutexGuard<'_, LruCache<String, CacheEntry>> {
+        self.lru.lock().unwrap()
+    }
+}
+
+impl ResponseCache for LruResponseCache {
+    #[inline]
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        let entry = self.lru().get(key).cloned();
+
+        self.counters.record(key, if entry.is_some() { Counter::Hits } else { Counter::Misses });
+
+        entry
+    }
+
+    #[inline]
+    fn peek(&self, key: &str) -> Option<CacheEntry> {
+        self.lru().peek(key).cloned()
+    }
+
+    #[inline]
+    fn contains_key(&self, key: &str) -> bool {
+        self.lru().contains_key(key)
+    }
+
+    fn insert(&self, key: String, entry: CacheEntry) -> Option<CacheEntry> {
+        let mut lru = self.lru();
+
+        if !lru.contains_key(key.as_str()) && lru.len() >= self.capacity {
+            let lru_key = lru.peek_iter().last().map(|(key, _)| key.clone());
+
+            if let Some(lru_key) = lru_key {
+                lru.remove(lru_key.as_str());
+
+                self.counters.record(lru_key.as_str(), Counter::Evictions);
+            }
+        }
+
+        self.counters.record(key.as_str(), Counter::Insertions);
+
+        lru.insert(key, entry)
+    }
+
+    #[inline]
+    fn remove(&self, key: &str) -> Option<CacheEntry> {
+        self.lru().remove(key)
+    }
+
+    fn remove_prefix(&self, prefix: &str) -> usize {
+        let mut lru = self.lru();
+
+        let keys: Vec<String> = lru
+            .peek_iter()
+            .filter_map(|(key, _)| if key.starts_with(prefix) { Some(key.clone()) } else { None })
+            .collect();
+
+        for key in keys.iter() {
+            lru.remove(key.as_str());
+        }
+
+        keys.len()
+    }
+
+    #[inline]
+    fn clear(&self) {
+        self.lru().clear();
+    }
+
+    /// The caches are listed from the most recently used one to the least recently used one.
+    #[inline]
+    fn entries(&self) -> Vec<CacheEntryInfo> {
+        self.lru()
+            .peek_iter()
+            .map(|(key, entry)| CacheEntryInfo {
+                key:  key.clone(),
+                size: entry.html.len(),
+                age:  entry.age(),
+            })
+            .collect()
+    }
+
+    #[inline]
+    fn stats(&self) -> CacheStats {
+        let lru = self.lru();
+
+        self.counters
+            .snapshot(None, lru.peek_iter().map(|(key, entry)| (key.as_str(), entry.html.len())))
+            .unwrap()
+    }
+
+    #[inline]
+    fn record_not_modified(&self, key: &str) {
+        self.counters.record(key, Counter::NotModified);
+    }
+
+    #[inline]
+    fn track_stats_prefix(&self, prefix: &str) {
+        self.counters.track_prefix(prefix);
+    }
+
+    #[inline]
+    fn stats_prefix(&self, prefix: &str) -> Option<CacheStats> {
+        let lru = self.lru();
+
+        self.counters.snapshot(
+            Some(prefix),
+            lru.peek_iter().map(|(key, entry)| (key.as_str(), entry.html.len())),
+        )
+    }
+
+    #[inline]
+    fn reset_stats(&self) {
+        self.counters.reset();
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use crate::{
+    CacheEntry, CacheEntryInfo, CacheStats, EntityTag, EtagIfNoneMatch, FragmentCacheHelper,
+    HandlebarsContextManager, HandlebarsResponse,
+};
+
+impl HandlebarsContextManager {
+    /// Build a `HandlebarsResponse` from the cache. Caches which have expired or are rendered from templates which have been changed since are dropped.
+    #[inline]
+    pub fn build_from_cache<K: AsRef<str>>(
+        &self,
+        etag_if_none_match: &EtagIfNoneMatch<'_>,
+        key: K,
+    ) -> Option<HandlebarsResponse> {
+        let key = key.as_ref();
+
+        let entry = self.cache_table.get(key)?;
+
+        if entry.is_expired() || !self.template_versions().is_current(&entry.templates) {
+            self.cache_table.remove(key);
+
+            return None;
+        }
+
+        if etag_if_none_match.weak_eq(&entry.etag) {
+            self.cache_table.record_not_modified(key);
+
+            Some(HandlebarsResponse::not_modified())
+        } else {
+            Some(HandlebarsResponse::build_cache(entry.html, &entry.etag))
+        }
+    }
+
+    /// Clear cache.
+    #[inline]
+    pub fn clear_cache(&self) {
+        self.cache_table.clear();
+    }
+
+    /// Check if a cache key exists.
+    #[inline]
+    pub fn contains_key<S: AsRef<str>>(&self, key: S) -> bool {
+        self.cache_table.contains_key(key.as_ref())
+    }
+
+    /// Get the cache by a specific key.
+    #[inline]
+    pub fn get<S: AsRef<str>>(&self, key: S) -> Option<(Arc<str>, Arc<EntityTag<'static>>)> {
+        self.cache_table.get(key.as_ref()).map(|entry| (entry.html, entry.etag))
+    }
+
+    /// Insert a cache.
+    #[inline]
+    pub fn insert<S: Into<String>>(
+        &self,
+        key: S,
+        cache: (Arc<str>, Arc<EntityTag<'static>>),
+    ) -> Option<(Arc<str>, Arc<EntityTag<'static>>)> {
+        let (html, etag) = cache;
+
+        self.cache_table
+            .insert(key.into(), CacheEntry::new(html, etag))
+            .map(|entry| (entry.html, entry.etag))
+    }
+
+    /// Cache the HTML and ETag of a `HandlebarsResponse` along with the templates it is rendered from, and return the response to send.
+    #[doc(hidden)]
+    #[inline]
+    pub fn cache_response<S: Into<String>>(
+        &self,
+        key: S,
+        mut res: HandlebarsResponse,
+    ) -> HandlebarsResponse {
+        let templates = res.templates.take();
+
+        match res.into_html_and_etag() {
+            Some((html, etag)) => {
+                let res = HandlebarsResponse::build_cache(html.clone(), &etag);
+
+                let mut entry = CacheEntry::new(html, Arc::new(etag));
+
+                if let Some(templates) = templates {
+                    entry = entry.with_templates(templates);
+                }
+
+                self.cache_table.insert(key.into(), entry);
+
+                res
+            },
+            None => HandlebarsResponse::not_modified(),
+        }
+    }
+
+    /// Remove the cache by a specific key.
+    #[inline]
+    pub fn remove<S: AsRef<str>>(&self, key: S) -> Option<(Arc<str>, Arc<EntityTag<'static>>)> {
+        self.cache_table.remove(key.as_ref()).map(|entry| (entry.html, entry.etag))
+    }
+
+    /// Remove all caches whose keys start with a specific prefix. Return the number of removed caches.
+    #[inline]
+    pub fn remove_prefix<S: AsRef<str>>(&self, prefix: S) -> usize {
+        self.cache_table.remove_prefix(prefix.as_ref())
+    }
+
+    #[inline]
+    pub(crate) fn fragment_cache_helper(&self) -> FragmentCacheHelper {
+        FragmentCacheHelper {
+            cache_table:                           self.cache_table.clone(),
+            template_versions:                     self.template_versions.clone(),
+            #[cfg(feature = "i18n")]
+            translations:                          self.translations.clone(),
+        }
+    }
+
+    /// Remove all caches which are rendered from a specific template, including the caches of templates using it as a partial. Return the number of removed caches.
+    #[inline]
+    pub fn remove_template_caches<S: AsRef<str>>(&self, name: S) -> usize {
+        let name = name.as_ref();
+
+        self.cache_table
+            .remove_if(&|_, entry| entry.templates.iter().any(|template| template.name == name))
+    }
+
+    /// Get the information of all caches. The recency of caches is not changed.
+    #[inline]
+    pub fn entries(&self) -> Vec<CacheEntryInfo> {
+        self.cache_table.entries()
+    }
+
+    /// Get a snapshot of the statistics of the cache.
+    #[inline]
+    pub fn stats(&self) -> CacheStats {
+        self.cache_table.stats()
+    }
+
+    /// Start recording the statistics of caches whose keys start with a specific prefix.
+    #[inline]
+    pub fn track_stats_prefix<S: AsRef<str>>(&self, prefix: S) {
+        self.cache_table.track_stats_prefix(prefix.as_ref());
+    }
+
+    /// Get a snapshot of the statistics of caches whose keys start with a specific prefix. The prefix needs to be tracked by `track_stats_prefix` first.
+    #[inline]
+    pub fn stats_prefix<S: AsRef<str>>(&self, prefix: S) -> Option<CacheStats> {
+        self.cache_table.stats_prefix(prefix.as_ref())
+    }
+
+    /// Reset the counters of the statistics.
+    #[inline]
+    pub fn reset_stats(&self) {
+        self.cache_table.reset_stats();
+    }
+}
@@ -0,0 +1,201 @@
+mod codec;
+
+mod counters;
+
+mod fragment;
+
+mod lru;
+
+mod sharded;
+
+mod key;
+
+mod key_value;
+
+#[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+mod manager;
+
+#[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+mod versions;
+
+mod warmer;
+
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+pub(crate) use codec::*;
+pub(crate) use counters::*;
+pub use fragment::*;
+pub use key::*;
+pub use key_value::*;
+pub use lru::*;
+pub use sharded::*;
+#[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+pub(crate) use versions::*;
+pub use warmer::*;
+
+use crate::EntityTag;
+
+/// A cached response.
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    /// The rendered HTML.
+    pub html:       Arc<str>,
+    /// The ETag of the HTML.
+    pub etag:       Arc<EntityTag<'static>>,
+    /// When this cache was created.
+    pub created_at: SystemTime,
+    /// When this cache expires. `None` means it never expires.
+    pub expires_at: Option<SystemTime>,
+    /// The templates which the HTML is rendered from.
+    pub templates:  Arc<[TemplateVersion]>,
+}
+
+impl CacheEntry {
+    /// Create a `CacheEntry` which is created now.
+    #[inline]
+    pub fn new(html: Arc<str>, etag: Arc<EntityTag<'static>>) -> CacheEntry {
+        CacheEntry {
+            html,
+            etag,
+            created_at: SystemTime::now(),
+            expires_at: None,
+            templates: Arc::new([]),
+        }
+    }
+
+    /// Set the templates which the HTML is rendered from. The cache is dropped when any of them is changed.
+    #[inline]
+    pub fn with_templates(mut self, templates: Arc<[TemplateVersion]>) -> CacheEntry {
+        self.templates = templates;
+
+        self
+    }
+
+    /// Make this cache expire after a specific duration since it was created.
+    #[inline]
+    pub fn with_ttl(mut self, ttl: Duration) -> CacheEntry {
+        self.expires_at = self.created_at.checked_add(ttl);
+
+        self
+    }
+
+    /// Check if this cache has expired.
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => SystemTime::now() >= expires_at,
+            None => false,
+        }
+    }
+
+    /// How long ago this cache was created.
+    #[inline]
+    pub fn age(&self) -> Duration {
+        SystemTime::now().duration_since(self.created_at).unwrap_or_default()
+    }
+}
+
+/// A template which a cache is rendered from, and the hash of its content at that time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateVersion {
+    /// The name of the template.
+    pub name: String,
+    /// The hash of the content of the template. It is empty if the template is not registered.
+    pub hash: String,
+}
+
+/// The information of a cache entry.
+#[derive(Debug, Clone)]
+pub struct CacheEntryInfo {
+    /// The key of this cache.
+    pub key:  String,
+    /// The length of the cached HTML in bytes.
+    pub size: usize,
+    /// How long ago this cache was inserted.
+    pub age:  Duration,
+}
+
+/// A snapshot of the statistics of the response cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of lookups which found a cache.
+    pub hits:         u64,
+    /// The number of lookups which did not find a cache.
+    pub misses:       u64,
+    /// The number of caches which have been inserted.
+    pub insertions:   u64,
+    /// The number of caches which have been evicted because the cache table was full.
+    pub evictions:    u64,
+    /// The number of **304 Not Modified** responses served from caches.
+    pub not_modified: u64,
+    /// The number of caches currently held.
+    pub entries:      usize,
+    /// The total length of the HTML currently held, in bytes.
+    pub bytes:        usize,
+}
+
+/// The storage of the caches of `HandlebarsResponse`s.
+pub trait ResponseCache: Send + Sync {
+    /// Get a cache by a specific key, update its recency and record a hit or a miss.
+    fn get(&self, key: &str) -> Option<CacheEntry>;
+
+    /// Get a cache by a specific key. The recency and the statistics are not changed.
+    fn peek(&self, key: &str) -> Option<CacheEntry>;
+
+    /// Check if a cache key exists. The recency and the statistics are not changed.
+    fn contains_key(&self, key: &str) -> bool;
+
+    /// Insert a cache and return the replaced one.
+    fn insert(&self, key: String, entry: CacheEntry) -> Option<CacheEntry>;
+
+    /// Remove the cache by a specific key.
+    fn remove(&self, key: &str) -> Option<CacheEntry>;
+
+    /// Remove all caches whose keys start with a specific prefix. Return the number of removed caches.
+    fn remove_prefix(&self, prefix: &str) -> usize;
+
+    /// Remove all caches.
+    fn clear(&self);
+
+    /// Remove all caches which `f` returns `true` for. Return the number of removed caches.
+    fn remove_if(&self, f: &dyn Fn(&str, &CacheEntry) -> bool) -> usize {
+        let mut count = 0;
+
+        for info in self.entries() {
+            if let Some(entry) = self.peek(&info.key) {
+                if f(&info.key, &entry) && self.remove(&info.key).is_some() {
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Get the information of all caches. The recency of caches is not changed.
+    fn entries(&self) -> Vec<CacheEntryInfo>;
+
+    /// Get a snapshot of the statistics.
+    fn stats(&self) -> CacheStats;
+
+    /// Record a **304 Not Modified** response served from the cache of a specific key.
+    #[inline]
+    fn record_not_modified(&self, _key: &str) {}
+
+    /// Start recording the statistics of caches whose keys start with a specific prefix.
+    #[inline]
+    fn track_stats_prefix(&self, _prefix: &str) {}
+
+    /// Get a snapshot of the statistics of caches whose keys start with a specific prefix.
+    #[inline]
+    fn stats_prefix(&self, _prefix: &str) -> Option<CacheStats> {
+        None
+    }
+
+    /// Reset the counters of the statistics.
+    #[inline]
+    fn reset_stats(&self) {}
+}
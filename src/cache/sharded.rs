@@ -0,0 +1,339 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hash, Hasher},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::Instant,
+};
+
+use super::{CacheEntry, CacheEntryInfo, CacheStats, Counter, ResponseCache, StatsCounters};
+
+/// The number of caches compared to choose the one to evict.
+const EVICTION_SAMPLES: usize = 8;
+
+#[derive(Debug)]
+struct Slot {
+    key:         Arc<str>,
+    entry:       CacheEntry,
+    /// The tick of the clock of the shard when this cache was last used.
+    last_access: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    /// The index of each cache in `slots`.
+    indexes: HashMap<Arc<str>, usize>,
+    slots:   Vec<Slot>,
+    /// Where the next eviction starts sampling.
+    hand:    usize,
+}
+
+impl Table {
+    #[inline]
+    fn get(&self, key: &str) -> Option<&Slot> {
+        self.indexes.get(key).map(|index| &self.slots[*index])
+    }
+
+    #[inline]
+    fn remove_at(&mut self, index: usize) -> Slot {
+        let slot = self.slots.swap_remove(index);
+
+        self.indexes.remove(&slot.key);
+
+        if let Some(moved) = self.slots.get(index) {
+            if let Some(moved_index) = self.indexes.get_mut(&moved.key) {
+                *moved_index = index;
+            }
+        }
+
+        slot
+    }
+
+    #[inline]
+    fn remove(&mut self, key: &str) -> Option<Slot> {
+        let index = *self.indexes.get(key)?;
+
+        Some(self.remove_at(index))
+    }
+
+    /// Remove the least recently used cache among a few caches, so that an eviction takes constant time. The sampled caches move on after every eviction, so every cache is compared sooner or later.
+    fn evict(&mut self) -> Option<Slot> {
+        let len = self.slots.len();
+
+        if len == 0 {
+            return None;
+        }
+
+        let start = self.hand % len;
+        let end = start + EVICTION_SAMPLES.min(len);
+
+        let index = (start..end)
+            .map(|i| i % len)
+            .min_by_key(|i| self.slots[*i].last_access.load(Ordering::Relaxed))?;
+
+        self.hand = end;
+
+        Some(self.remove_at(index))
+    }
+
+    fn remove_prefix(&mut self, prefix: &str) -> usize {
+        let len = self.slots.len();
+
+        // a removed slot is replaced by the last one, which has been checked
+        for index in (0..len).rev() {
+            if self.slots[index].key.starts_with(prefix) {
+                self.remove_at(index);
+            }
+        }
+
+        len - self.slots.len()
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.indexes.clear();
+        self.slots.clear();
+    }
+}
+
+// Aligned to keep the clocks and the counters of different shards out of the same cache line.
+#[derive(Debug)]
+#[repr(align(128))]
+struct Shard {
+    capacity: usize,
+    table:    RwLock<Table>,
+    clock:    AtomicU64,
+    counters: StatsCounters,
+}
+
+impl Shard {
+    /// Get the nanoseconds since `epoch`. The ticks of a shard are strictly increasing even if the time source is coarse, and the ticks of different shards are comparable without sharing a counter.
+    #[inline]
+    fn tick(&self, epoch: Instant) -> u64 {
+        let now = epoch.elapsed().as_nanos() as u64;
+
+        if self.clock.fetch_max(now, Ordering::Relaxed) < now {
+            now
+        } else {
+            self.clock.fetch_add(1, Ordering::Relaxed) + 1
+        }
+    }
+}
+
+/// The default `ResponseCache`, which keeps caches in memory and splits them into shards by their keys, so that lookups of different keys rarely contend for the same lock. Looking up a cache only takes a read lock, and every shard has its own clock, which follows the time since the cache was created, and its own statistics counters, which are summed up by `stats`. When a shard is full, the least recently used one of a few of its caches is evicted, so the eviction is an approximation of LRU over the whole cache, and it takes constant time.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct ShardedResponseCache {
+    shards: Box<[Shard]>,
+    epoch:  Instant,
+    #[educe(Debug(ignore))]
+    hasher: RandomState,
+}
+
+impl ShardedResponseCache {
+    /// Create an instance of `ShardedResponseCache` which can hold at most `capacity` caches. The number of shards is decided by the available parallelism and the capacity.
+    #[inline]
+    pub fn new(capacity: usize) -> ShardedResponseCache {
+        let parallelism = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+        // keep at least 8 caches in a shard, otherwise the eviction is too inaccurate
+        let shard_count = (parallelism * 4).min(capacity / 8).max(1);
+
+        ShardedResponseCache::with_shards(capacity, shard_count)
+    }
+
+    /// Create an instance of `ShardedResponseCache` which can hold at most `capacity` caches in `shard_count` shards. There are no more shards than `capacity`, so that every shard can hold a cache.
+    pub fn with_shards(capacity: usize, shard_count: usize) -> ShardedResponseCache {
+        let shard_count = shard_count.clamp(1, capacity.max(1));
+
+        let shards = (0..shard_count)
+            .map(|i| {
+                let capacity =
+                    capacity / shard_count + if i < capacity % shard_count { 1 } else { 0 };
+
+                Shard {
+                    capacity,
+                    table: RwLock::new(Table {
+                        indexes: HashMap::with_capacity(capacity),
+                        slots:   Vec::with_capacity(capacity),
+                        hand:    0,
+                    }),
+                    clock: AtomicU64::new(0),
+                    counters: StatsCounters::default(),
+                }
+            })
+            .collect();
+
+        ShardedResponseCache {
+            shards,
+            epoch: Instant::now(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    #[inline]
+    fn shard(&self, key: &str) -> &Shard {
+        let mut hasher = self.hasher.build_hasher();
+
+        key.hash(&mut hasher);
+
+        &self.shards[(hasher.finish() % self.shards.len() as u64) as usize]
+    }
+
+    /// Sum up the statistics of all shards. Return `None` if the prefix is not tracked.
+    fn collect_stats(&self, prefix: Option<&str>) -> Option<CacheStats> {
+        let mut stats = CacheStats::default();
+
+        for shard in self.shards.iter() {
+            if !shard.counters.add_to(prefix, &mut stats) {
+                return None;
+            }
+
+            for slot in shard.table.read().unwrap().slots.iter() {
+                if prefix.map(|prefix| slot.key.starts_with(prefix)).unwrap_or(true) {
+                    stats.entries += 1;
+                    stats.bytes += slot.entry.html.len();
+                }
+            }
+        }
+
+        Some(stats)
+    }
+}
+
+impl ResponseCache for ShardedResponseCache {
+    #[inline]
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        let shard = self.shard(key);
+
+        let entry = shard.table.read().unwrap().get(key).map(|slot| {
+            slot.last_access.store(shard.tick(self.epoch), Ordering::Relaxed);
+
+            slot.entry.clone()
+        });
+
+        shard.counters.record(key, if entry.is_some() { Counter::Hits } else { Counter::Misses });
+
+        entry
+    }
+
+    #[inline]
+    fn peek(&self, key: &str) -> Option<CacheEntry> {
+        self.shard(key).table.read().unwrap().get(key).map(|slot| slot.entry.clone())
+    }
+
+    #[inline]
+    fn contains_key(&self, key: &str) -> bool {
+        self.shard(key).table.read().unwrap().indexes.contains_key(key)
+    }
+
+    fn insert(&self, key: String, entry: CacheEntry) -> Option<CacheEntry> {
+        let shard = self.shard(key.as_str());
+
+        if shard.capacity == 0 {
+            return None;
+        }
+
+        let mut table = shard.table.write().unwrap();
+
+        shard.counters.record(key.as_str(), Counter::Insertions);
+
+        let last_access = AtomicU64::new(shard.tick(self.epoch));
+
+        if let Some(index) = table.indexes.get(key.as_str()).copied() {
+            let slot = &mut table.slots[index];
+
+            slot.last_access = last_access;
+
+            return Some(std::mem::replace(&mut slot.entry, entry));
+        }
+
+        if table.slots.len() >= shard.capacity {
+            if let Some(slot) = table.evict() {
+                shard.counters.record(&slot.key, Counter::Evictions);
+            }
+        }
+
+        let key: Arc<str> = Arc::from(key);
+
+        let index = table.slots.len();
+
+        table.indexes.insert(key.clone(), index);
+        table.slots.push(Slot {
+            key,
+            entry,
+            last_access,
+        });
+
+        None
+    }
+
+    #[inline]
+    fn remove(&self, key: &str) -> Option<CacheEntry> {
+        self.shard(key).table.write().unwrap().remove(key).map(|slot| slot.entry)
+    }
+
+    fn remove_prefix(&self, prefix: &str) -> usize {
+        self.shards.iter().map(|shard| shard.table.write().unwrap().remove_prefix(prefix)).sum()
+    }
+
+    #[inline]
+    fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.table.write().unwrap().clear();
+        }
+    }
+
+    /// The caches are listed from the most recently used one to the least recently used one.
+    fn entries(&self) -> Vec<CacheEntryInfo> {
+        let mut entries = Vec::new();
+
+        for shard in self.shards.iter() {
+            entries.extend(shard.table.read().unwrap().slots.iter().map(|slot| {
+                (
+                    CacheEntryInfo {
+                        key:  String::from(slot.key.as_ref()),
+                        size: slot.entry.html.len(),
+                        age:  slot.entry.age(),
+                    },
+                    slot.last_access.load(Ordering::Relaxed),
+                )
+            }));
+        }
+
+        entries.sort_unstable_by(|(_, a), (_, b)| b.cmp(a));
+
+        entries.into_iter().map(|(info, _)| info).collect()
+    }
+
+    #[inline]
+    fn stats(&self) -> CacheStats {
+        self.collect_stats(None).unwrap()
+    }
+
+    #[inline]
+    fn record_not_modified(&self, key: &str) {
+        self.shard(key).counters.record(key, Counter::NotModified);
+    }
+
+    #[inline]
+    fn track_stats_prefix(&self, prefix: &str) {
+        for shard in self.shards.iter() {
+            shard.counters.track_prefix(prefix);
+        }
+    }
+
+    #[inline]
+    fn stats_prefix(&self, prefix: &str) -> Option<CacheStats> {
+        self.collect_stats(Some(prefix))
+    }
+
+    #[inline]
+    fn reset_stats(&self) {
+        for shard in self.shards.iter() {
+            shard.counters.reset();
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    sync::Arc,
+};
+
+use handlebars::{
+    template::{
+        BlockParam, DecoratorTemplate, HelperTemplate, Parameter, Template, TemplateElement,
+    },
+    Handlebars, JsonValue,
+};
+
+use super::TemplateVersion;
+use crate::functions::compute_data_etag;
+
+/// The versions of all registered templates, and the partials each template uses.
+#[derive(Debug, Default)]
+pub(crate) struct TemplateVersions {
+    hashes:       HashMap<String, String>,
+    /// The version of a template itself, followed by the versions of the partials it uses.
+    dependencies: HashMap<String, Arc<[TemplateVersion]>>,
+}
+
+impl TemplateVersions {
+    pub(crate) fn new(handlebars: &Handlebars) -> TemplateVersions {
+        let templates = handlebars.get_templates();
+
+        let hashes: HashMap<String, String> = templates
+            .iter()
+            .map(|(name, template)| (name.clone(), compute_template_hash(template)))
+            .collect();
+
+        let dependencies = templates
+            .keys()
+            .map(|name| {
+                let mut names = vec![name.as_str()];
+                let mut visited: HashSet<&str> = names.iter().copied().collect();
+
+                let mut i = 0;
+
+                while i < names.len() {
+                    if let Some(template) = templates.get(names[i]) {
+                        let mut partials = Vec::new();
+
+                        collect_partials(&template.elements, &mut partials);
+
+                        // `@partial-block` is the block passed to the partial, which is already in the template using the partial
+                        for partial in partials {
+                            if !partial.starts_with('@') && visited.insert(partial) {
+                                names.push(partial);
+                            }
+                        }
+                    }
+
+                    i += 1;
+                }
+
+                let versions = names
+                    .into_iter()
+                    .map(|name| TemplateVersion {
+                        name: String::from(name),
+                        hash: hashes.get(name).cloned().unwrap_or_default(),
+                    })
+                    .collect();
+
+                (name.clone(), versions)
+            })
+            .collect();
+
+        TemplateVersions {
+            hashes,
+            dependencies,
+        }
+    }
+
+    /// Get the versions of a template and the partials it uses.
+    #[inline]
+    pub(crate) fn get(&self, name: &str) -> Option<Arc<[TemplateVersion]>> {
+        self.dependencies.get(name).cloned()
+    }
+
+    /// Check if all templates are still the same as `versions`.
+    #[inline]
+    pub(crate) fn is_current(&self, versions: &[TemplateVersion]) -> bool {
+        versions.iter().all(|version| {
+            self.hashes.get(version.name.as_str()).map(String::as_str).unwrap_or_default()
+                == version.hash
+        })
+    }
+
+    /// Compute a fingerprint of all templates.
+    #[cfg(not(debug_assertions))]
+    pub(crate) fn fingerprint(&self) -> String {
+        let mut hashes: Vec<_> = self.hashes.iter().collect();
+
+        hashes.sort_unstable();
+
+        let mut data = String::new();
+
+        for (name, hash) in hashes {
+            writeln!(data, "{}\n{}", name, hash).unwrap();
+        }
+
+        compute_data_etag(&data).to_string()
+    }
+}
+
+impl PartialEq for TemplateVersions {
+    #[inline]
+    fn eq(&self, other: &TemplateVersions) -> bool {
+        self.hashes == other.hashes
+    }
+}
+
+/// Hash a template by its syntax tree, so that the hash does not change between processes.
+fn compute_template_hash(template: &Template) -> String {
+    let mut data = String::new();
+
+    write_elements(&mut data, &template.elements);
+
+    compute_data_etag(&data).get_tag().to_string()
+}
+
+// The `Debug` output of a template cannot be hashed directly, because hash arguments are stored in `HashMap`s whose order is random.
+
+fn write_elements(data: &mut String, elements: &[TemplateElement]) {
+    for element in elements {
+        match element {
+            TemplateElement::RawString(s) => write!(data, "R{:?}", s).unwrap(),
+            TemplateElement::HtmlExpression(helper) => {
+                data.push_str("H(");
+                write_helper(data, helper);
+                data.push(')');
+            },
+            TemplateElement::Expression(helper) => {
+                data.push_str("E(");
+                write_helper(data, helper);
+                data.push(')');
+            },
+            TemplateElement::HelperBlock(helper) => {
+                data.push_str("B(");
+                write_helper(data, helper);
+                data.push(')');
+            },
+            TemplateElement::DecoratorExpression(decorator) => {
+                data.push_str("DE(");
+                write_decorator(data, decorator);
+                data.push(')');
+            },
+            TemplateElement::DecoratorBlock(decorator) => {
+                data.push_str("DB(");
+                write_decorator(data, decorator);
+                data.push(')');
+            },
+            TemplateElement::PartialExpression(decorator) => {
+                data.push_str("PE(");
+                write_decorator(data, decorator);
+                data.push(')');
+            },
+            TemplateElement::PartialBlock(decorator) => {
+                data.push_str("PB(");
+                write_decorator(data, decorator);
+                data.push(')');
+            },
+            TemplateElement::Comment(s) => write!(data, "C{:?}", s).unwrap(),
+        }
+    }
+}
+
+fn write_helper(data: &mut String, helper: &HelperTemplate) {
+    write_parameter(data, &helper.name);
+    write_parameters(data, &helper.params, &helper.hash);
+
+    match &helper.block_param {
+        Some(BlockParam::Single(param)) => {
+            data.push_str("as|");
+            write_parameter(data, param);
+        },
+        Some(BlockParam::Pair((a, b))) => {
+            data.push_str("as|");
+            write_parameter(data, a);
+            write_parameter(data, b);
+        },
+        None => (),
+    }
+
+    write!(data, "{}", helper.block).unwrap();
+
+    write_template(data, helper.template.as_ref());
+    write_template(data, helper.inverse.as_ref());
+}
+
+fn write_decorator(data: &mut String, decorator: &DecoratorTemplate) {
+    write_parameter(data, &decorator.name);
+    write_parameters(data, &decorator.params, &decorator.hash);
+
+    write!(data, "{:?}", decorator.indent).unwrap();
+
+    write_template(data, decorator.template.as_ref());
+}
+
+fn write_template(data: &mut String, template: Option<&Template>) {
+    match template {
+        Some(template) => {
+            data.push('{');
+            write_elements(data, &template.elements);
+            data.push('}');
+        },
+        None => data.push('-'),
+    }
+}
+
+fn write_parameters(data: &mut String, params: &[Parameter], hash: &HashMap<String, Parameter>) {
+    for param in params {
+        write_parameter(data, param);
+    }
+
+    let mut hash: Vec<_> = hash.iter().collect();
+
+    hash.sort_unstable_by_key(|(name, _)| *name);
+
+    for (name, param) in hash {
+        write!(data, "{:?}=", name).unwrap();
+        write_parameter(data, param);
+    }
+}
+
+fn write_parameter(data: &mut String, param: &Parameter) {
+    match param {
+        Parameter::Subexpression(subexpression) => {
+            data.push('(');
+            write_elements(data, std::slice::from_ref(subexpression.as_element()));
+            data.push(')');
+        },
+        _ => write!(data, "{:?} ", param).unwrap(),
+    }
+}
+
+/// Collect the names of the partials which are used in the elements. Partials whose names are computed at render time cannot be known.
+fn collect_partials<'a>(elements: &'a [TemplateElement], partials: &mut Vec<&'a str>) {
+    for element in elements {
+        match element {
+            TemplateElement::HtmlExpression(helper)
+            | TemplateElement::Expression(helper)
+            | TemplateElement::HelperBlock(helper) => {
+                for template in helper.template.iter().chain(helper.inverse.iter()) {
+                    collect_partials(&template.elements, partials);
+                }
+            },
+            TemplateElement::PartialExpression(decorator)
+            | TemplateElement::PartialBlock(decorator) => {
+                match &decorator.name {
+                    Parameter::Literal(JsonValue::String(name)) => partials.push(name),
+                    name => {
+                        if let Some(name) = name.as_name() {
+                            partials.push(name);
+                        }
+                    },
+                }
+
+                if let Some(template) = decorator.template.as_ref() {
+                    collect_partials(&template.elements, partials);
+                }
+            },
+            TemplateElement::DecoratorExpression(decorator)
+            | TemplateElement::DecoratorBlock(decorator) => {
+                if let Some(template) = decorator.template.as_ref() {
+                    collect_partials(&template.elements, partials);
+                }
+            },
+            TemplateElement::RawString(_) | TemplateElement::Comment(_) => (),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    error::Error,
+    panic::{self, AssertUnwindSafe},
+};
+
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    tokio::{
+        runtime::{Handle, RuntimeFlavor},
+        task,
+    },
+    Build, Rocket,
+};
+
+use crate::{HandlebarsContextManager, HandlebarsResponse};
+
+const WARMING_FAIRING_NAME: &str = "Handlebars Cache Warming";
+
+/// The result of a generator of `CacheWarmer`.
+pub type WarmingResult = Result<HandlebarsResponse, Box<dyn Error + Send + Sync>>;
+
+type Generator = Box<dyn Fn(&HandlebarsContextManager) -> WarmingResult + Send + Sync>;
+
+struct WarmingEntry {
+    key:       String,
+    required:  bool,
+    generator: Generator,
+}
+
+/// A list of cache keys and the constructors of their `HandlebarsResponse`s, which are used to generate caches at ignite.
+#[derive(Default)]
+pub struct CacheWarmer {
+    entries: Vec<WarmingEntry>,
+}
+
+impl CacheWarmer {
+    /// Generate a cache at ignite. If the generator returns an error, returns a **304 Not Modified** response or panics, a warning is logged.
+    #[inline]
+    pub fn warm<K: Into<String>, G>(&mut self, key: K, generator: G) -> &mut Self
+    where
+        G: Fn(&HandlebarsContextManager) -> WarmingResult + Send + Sync + 'static, {
+        self.push(key.into(), false, Box::new(generator))
+    }
+
+    /// Generate a cache at ignite. If the generator returns an error, returns a **304 Not Modified** response or panics, Rocket fails to launch.
+    #[inline]
+    pub fn warm_required<K: Into<String>, G>(&mut self, key: K, generator: G) -> &mut Self
+    where
+        G: Fn(&HandlebarsContextManager) -> WarmingResult + Send + Sync + 'static, {
+        self.push(key.into(), true, Box::new(generator))
+    }
+
+    #[inline]
+    fn push(&mut self, key: String, required: bool, generator: Generator) -> &mut Self {
+        self.entries.push(WarmingEntry {
+            key,
+            required,
+            generator,
+        });
+
+        self
+    }
+
+    /// Run all generators and cache their responses. Return `false` if any required generator fails.
+    pub(crate) fn warm_up(&self, cm: &HandlebarsContextManager) -> bool {
+        let mut success = true;
+
+        for entry in self.entries.iter() {
+            let result = match panic::catch_unwind(AssertUnwindSafe(|| (entry.generator)(cm))) {
+                Ok(Ok(res)) if res.is_not_modified() => Err(String::from(
+                    "the response is 304 Not Modified, so it needs to be built with \
+                     `EtagIfNoneMatch::default()`",
+                )),
+                Ok(Ok(res)) => {
+                    cm.cache_response(entry.key.as_str(), res);
+
+                    Ok(())
+                },
+                Ok(Err(err)) => Err(err.to_string()),
+                Err(payload) => Err(payload
+                    .downcast_ref::<&str>()
+                    .map(|message| String::from(*message))
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| String::from("unknown error"))),
+            };
+
+            if let Err(message) = result {
+                if entry.required {
+                    rocket::error_!("Cannot warm the cache `{}` up: {}", entry.key, message);
+
+                    success = false;
+                } else {
+                    rocket::warn_!("Cannot warm the cache `{}` up: {}", entry.key, message);
+                }
+            }
+        }
+
+        success
+    }
+}
+
+/// The fairing which generates caches at ignite.
+pub struct HandlebarsCacheWarmingFairing {
+    pub(crate) custom_callback: Box<dyn Fn(&mut CacheWarmer) + Send + Sync + 'static>,
+}
+
+#[rocket::async_trait]
+impl Fairing for HandlebarsCacheWarmingFairing {
+    #[inline]
+    fn info(&self) -> Info {
+        Info {
+            name: WARMING_FAIRING_NAME, kind: Kind::Ignite
+        }
+    }
+
+    #[inline]
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> Result<Rocket<Build>, Rocket<Build>> {
+        let cm = match rocket.state::<HandlebarsContextManager>() {
+            Some(cm) => cm,
+            None => {
+                rocket::error_!(
+                    "The fairing of `HandlebarsResponse` needs to be attached before the cache \
+                     warming fairing."
+                );
+
+                return Err(rocket);
+            },
+        };
+
+        let mut warmer = CacheWarmer::default();
+
+        (self.custom_callback)(&mut warmer);
+
+        let warm_up = || warmer.warm_up(cm);
+
+        // rendering blocks the thread, so let the multi-threaded runtime move its other tasks away first
+        let success = match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+            Ok(RuntimeFlavor::MultiThread) => task::block_in_place(warm_up),
+            _ => warm_up(),
+        };
+
+        if success {
+            Ok(rocket)
+        } else {
+            Err(rocket)
+        }
+    }
+}
+
+impl HandlebarsResponse {
+    /// Create the fairing which generates caches at ignite. It needs to be attached after the fairing of `HandlebarsResponse`. The cache is generated only when you are using the **release** profile, but the generators are always run so that their failures can be found.
+    ///
+    /// The generators are run one by one before Rocket launches, and they block the thread they are run on. On a multi-threaded runtime, which Rocket uses by default, the thread is handed over by `tokio::task::block_in_place` first, so the other tasks of the runtime are not blocked.
+    #[inline]
+    pub fn cache_warming_fairing<F>(f: F) -> impl Fairing
+    where
+        F: Fn(&mut CacheWarmer) + Send + Sync + 'static, {
+        HandlebarsCacheWarmingFairing {
+            custom_callback: Box::new(f)
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::{
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
 
 use rocket::{
     data::Data,
@@ -8,14 +11,20 @@ use rocket::{
 };
 
 use super::{HandlebarsContextManager, HandlebarsResponse, ReloadableHandlebars};
+use crate::{ResponseCache, ShardedResponseCache, UrlHelper};
 
 const FAIRING_NAME: &str = "Handlebars (Debug)";
+const SNAPSHOT_FAIRING_NAME: &str = "Handlebars Cache Snapshot (Debug)";
 
 /// The fairing of `HandlebarsResponse`.
 pub struct HandlebarsResponseFairing {
     #[allow(clippy::type_complexity)]
-    pub(crate) custom_callback:
-        Box<dyn Fn(&mut MutexGuard<ReloadableHandlebars>) -> usize + Send + Sync + 'static>,
+    pub(crate) custom_callback: Box<
+        dyn Fn(&mut MutexGuard<ReloadableHandlebars>) -> Box<dyn ResponseCache>
+            + Send
+            + Sync
+            + 'static,
+    >,
 }
 
 #[rocket::async_trait]
@@ -29,12 +38,28 @@ impl Fairing for HandlebarsResponseFairing {
 
     #[inline]
     async fn on_ignite(&self, rocket: Rocket<Build>) -> Result<Rocket<Build>, Rocket<Build>> {
-        let handlebars = Mutex::new(ReloadableHandlebars::new());
+        let mut reloadable_handlebars = ReloadableHandlebars::new();
+
+        reloadable_handlebars.register_helper("url", Box::new(UrlHelper::new(rocket.routes())));
+
+        let handlebars = Mutex::new(reloadable_handlebars);
+
+        // drop translations registered out of a fairing, so that only the ones registered in the callback are taken by `HandlebarsContextManager::new`
+        #[cfg(feature = "i18n")]
+        crate::i18n::take_registered_translations();
 
-        let cache_capacity =
+        let cache_table =
             (self.custom_callback)(&mut handlebars.lock().unwrap_or_else(PoisonError::into_inner));
 
-        let state = HandlebarsContextManager::new(handlebars, cache_capacity);
+        let state = HandlebarsContextManager::new(handlebars, cache_table);
+
+        let fragment_cache_helper = state.fragment_cache_helper();
+
+        state
+            .handlebars
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .register_helper("cache", Box::new(fragment_cache_helper));
 
         Ok(rocket.manage(state))
     }
@@ -46,7 +71,24 @@ impl Fairing for HandlebarsResponseFairing {
             .state::<HandlebarsContextManager>()
             .expect("HandlebarsContextManager registered in on_attach");
 
-        cm.handlebars.lock().unwrap_or_else(PoisonError::into_inner).reload_if_needed().unwrap();
+        let mut handlebars = cm.handlebars.lock().unwrap_or_else(PoisonError::into_inner);
+
+        handlebars.reload_if_needed().unwrap();
+
+        #[cfg(feature = "debug_cache")]
+        cm.invalidate_cache_if_reloaded(&handlebars);
+    }
+}
+
+/// The fairing which restores the cache of `HandlebarsContextManager` from a file at ignite and saves the cache to the file on shutdown. The cache is generated only when you are using the **release** profile, so this fairing does nothing.
+pub struct HandlebarsCacheSnapshotFairing;
+
+impl Fairing for HandlebarsCacheSnapshotFairing {
+    #[inline]
+    fn info(&self) -> Info {
+        Info {
+            name: SNAPSHOT_FAIRING_NAME, kind: Kind::Singleton
+        }
     }
 }
 
@@ -62,7 +104,7 @@ impl HandlebarsResponse {
             custom_callback: Box::new(move |handlebars| {
                 f(handlebars);
 
-                crate::DEFAULT_CACHE_CAPACITY
+                Box::new(ShardedResponseCache::new(crate::DEFAULT_CACHE_CAPACITY))
             }),
         }
     }
@@ -73,7 +115,26 @@ impl HandlebarsResponse {
     where
         F: Fn(&mut MutexGuard<ReloadableHandlebars>) -> usize + Send + Sync + 'static, {
         HandlebarsResponseFairing {
-            custom_callback: Box::new(f)
+            custom_callback: Box::new(move |handlebars| {
+                Box::new(ShardedResponseCache::new(f(handlebars)))
+            }),
         }
     }
+
+    /// Create the fairing of `HandlebarsResponse` and set the storage of the cache.
+    #[inline]
+    pub fn fairing_custom_cache<F, C>(f: F) -> impl Fairing
+    where
+        F: Fn(&mut MutexGuard<ReloadableHandlebars>) -> C + Send + Sync + 'static,
+        C: ResponseCache + 'static, {
+        HandlebarsResponseFairing {
+            custom_callback: Box::new(move |handlebars| Box::new(f(handlebars))),
+        }
+    }
+
+    /// Create the fairing which restores the cache from a file at ignite and saves the cache to the file on shutdown. It needs to be attached after the fairing of `HandlebarsResponse`.
+    #[inline]
+    pub fn cache_snapshot_fairing<P: Into<PathBuf>>(_path: P) -> impl Fairing {
+        HandlebarsCacheSnapshotFairing
+    }
 }
@@ -1,4 +1,6 @@
 use std::io::Cursor;
+#[cfg(feature = "debug_cache")]
+use std::sync::Arc;
 
 use rocket::{
     http::Status,
@@ -6,6 +8,8 @@ use rocket::{
     response::{self, Responder, Response},
 };
 
+#[cfg(feature = "debug_cache")]
+use crate::TemplateVersion;
 use crate::{EntityTag, EtagIfNoneMatch};
 
 #[derive(Debug)]
@@ -17,29 +21,71 @@ struct HandlebarsResponseInner {
 #[derive(Debug)]
 /// To respond HTML.
 pub struct HandlebarsResponse {
-    inner: Option<HandlebarsResponseInner>,
+    inner:                Option<HandlebarsResponseInner>,
+    vary:                 Option<String>,
+    /// The templates which the HTML is rendered from.
+    #[cfg(feature = "debug_cache")]
+    pub(crate) templates: Option<Arc<[TemplateVersion]>>,
 }
 
 impl HandlebarsResponse {
+    #[inline]
+    const fn new(inner: Option<HandlebarsResponseInner>) -> HandlebarsResponse {
+        HandlebarsResponse {
+            inner,
+            vary: None,
+            #[cfg(feature = "debug_cache")]
+            templates: None,
+        }
+    }
+
     #[inline]
     pub(crate) fn build_not_cache<S: Into<String>>(
         content: S,
         etag: &EntityTag<'static>,
     ) -> HandlebarsResponse {
-        HandlebarsResponse {
-            inner: Some(HandlebarsResponseInner {
-                content: content.into(),
-                etag:    etag.to_string(),
-            }),
-        }
+        HandlebarsResponse::new(Some(HandlebarsResponseInner {
+            content: content.into(),
+            etag:    etag.to_string(),
+        }))
+    }
+
+    #[cfg(feature = "debug_cache")]
+    #[doc(hidden)]
+    #[inline]
+    pub fn build_cache(content: Arc<str>, etag: &EntityTag<'static>) -> HandlebarsResponse {
+        HandlebarsResponse::build_not_cache(content.as_ref(), etag)
+    }
+
+    #[cfg(feature = "debug_cache")]
+    #[inline]
+    pub(crate) fn with_templates(
+        mut self,
+        templates: Option<Arc<[TemplateVersion]>>,
+    ) -> HandlebarsResponse {
+        self.templates = templates;
+
+        self
+    }
+
+    /// Set the **Vary** header of this response.
+    #[inline]
+    pub fn vary<S: Into<String>>(mut self, vary: S) -> HandlebarsResponse {
+        self.vary = Some(vary.into());
+
+        self
     }
 
     #[doc(hidden)]
     #[inline]
     pub const fn not_modified() -> HandlebarsResponse {
-        HandlebarsResponse {
-            inner: None
-        }
+        HandlebarsResponse::new(None)
+    }
+
+    /// Check if this response is a **304 Not Modified** response.
+    #[inline]
+    pub const fn is_not_modified(&self) -> bool {
+        self.inner.is_none()
     }
 
     #[doc(hidden)]
@@ -54,6 +100,20 @@ impl HandlebarsResponse {
             })
             .unwrap_or(false)
     }
+
+    #[cfg(feature = "debug_cache")]
+    #[doc(hidden)]
+    #[inline]
+    pub fn into_html_and_etag(self) -> Option<(Arc<str>, EntityTag<'static>)> {
+        self.inner.map(|mut inner| {
+            inner.etag.remove(inner.etag.len() - 1);
+            inner.etag.remove(0);
+
+            let etag = unsafe { EntityTag::with_string_unchecked(false, inner.etag) };
+
+            (Arc::from(inner.content), etag)
+        })
+    }
 }
 
 impl<'r, 'o: 'r> Responder<'r, 'o> for HandlebarsResponse {
@@ -61,6 +121,10 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for HandlebarsResponse {
     fn respond_to(self, _: &'r Request<'_>) -> response::Result<'o> {
         let mut response = Response::build();
 
+        if let Some(vary) = self.vary {
+            response.raw_header("Vary", vary);
+        }
+
         if let Some(inner) = self.inner {
             response.raw_header("Content-Type", "text/html; charset=utf-8");
             response.raw_header("Etag", inner.etag);
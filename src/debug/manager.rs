@@ -1,28 +1,172 @@
-use std::sync::{Mutex, PoisonError};
+#[cfg(feature = "debug_cache")]
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, RwLock, RwLockReadGuard,
+};
+use std::{
+    borrow::Cow,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
 
+use handlebars::TemplateError;
 use serde::Serialize;
 
 use super::{HandlebarsResponse, ReloadableHandlebars};
-use crate::{functions::compute_data_etag, EtagIfNoneMatch};
+#[cfg(not(feature = "debug_cache"))]
+use crate::FragmentCacheHelper;
+#[cfg(feature = "debug_cache")]
+use crate::TemplateVersions;
+#[cfg(all(feature = "debug_cache", feature = "i18n"))]
+use crate::Translations;
+use crate::{functions::compute_data_etag, EtagIfNoneMatch, Locale, ResponseCache};
 
 /// To monitor the state of Handlebars.
 #[derive(Educe)]
 #[educe(Debug)]
 pub struct HandlebarsContextManager {
-    pub handlebars: Mutex<ReloadableHandlebars>,
+    pub handlebars:               Mutex<ReloadableHandlebars>,
+    #[cfg(feature = "debug_cache")]
+    #[educe(Debug(ignore))]
+    pub(crate) cache_table:       Arc<dyn ResponseCache>,
+    #[cfg(feature = "debug_cache")]
+    cache_generation:             AtomicU64,
+    #[cfg(feature = "debug_cache")]
+    pub(crate) template_versions: Arc<RwLock<TemplateVersions>>,
+    #[cfg(all(feature = "debug_cache", feature = "i18n"))]
+    pub(crate) translations:      Option<Arc<Translations>>,
 }
 
 impl HandlebarsContextManager {
     #[inline]
     pub(crate) fn new(
         handlebars: Mutex<ReloadableHandlebars>,
-        _cache_capacity: usize,
+        #[allow(unused_variables)] cache_table: Box<dyn ResponseCache>,
     ) -> HandlebarsContextManager {
+        #[cfg(feature = "debug_cache")]
+        let (cache_generation, template_versions) = {
+            let handlebars = handlebars.lock().unwrap_or_else(PoisonError::into_inner);
+
+            (
+                AtomicU64::new(handlebars.generation()),
+                Arc::new(RwLock::new(TemplateVersions::new(&handlebars))),
+            )
+        };
+
         HandlebarsContextManager {
             handlebars,
+            #[cfg(feature = "debug_cache")]
+            cache_table: Arc::from(cache_table),
+            #[cfg(feature = "debug_cache")]
+            cache_generation,
+            #[cfg(feature = "debug_cache")]
+            template_versions,
+            #[cfg(all(feature = "debug_cache", feature = "i18n"))]
+            translations: crate::i18n::take_registered_translations(),
         }
     }
 
+    #[cfg(not(feature = "debug_cache"))]
+    #[doc(hidden)]
+    #[inline]
+    pub fn cache_response<S: Into<String>>(
+        &self,
+        _key: S,
+        res: HandlebarsResponse,
+    ) -> HandlebarsResponse {
+        res
+    }
+
+    #[cfg(not(feature = "debug_cache"))]
+    #[inline]
+    pub(crate) fn fragment_cache_helper(&self) -> FragmentCacheHelper {
+        FragmentCacheHelper
+    }
+
+    #[cfg(feature = "debug_cache")]
+    #[inline]
+    pub(crate) fn template_versions(&self) -> RwLockReadGuard<'_, TemplateVersions> {
+        self.template_versions.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Remove all caches which are rendered from templates that have been changed or unregistered since. Caches which do not record their templates, such as the ones inserted by `insert`, are removed as well.
+    #[cfg(feature = "debug_cache")]
+    #[inline]
+    fn remove_stale_caches(&self) -> usize {
+        let template_versions = self.template_versions();
+
+        self.cache_table.remove_if(&|_, entry| {
+            entry.templates.is_empty() || !template_versions.is_current(&entry.templates)
+        })
+    }
+
+    /// Drop the caches rendered from templates which have been registered, reloaded or unregistered since the last call.
+    #[cfg(feature = "debug_cache")]
+    #[inline]
+    pub(crate) fn invalidate_cache_if_reloaded(&self, handlebars: &ReloadableHandlebars) {
+        let generation = handlebars.generation();
+
+        if self.cache_generation.swap(generation, Ordering::Relaxed) != generation {
+            let template_versions = TemplateVersions::new(handlebars);
+
+            let mut current =
+                self.template_versions.write().unwrap_or_else(PoisonError::into_inner);
+
+            if *current != template_versions {
+                *current = template_versions;
+
+                drop(current);
+
+                self.remove_stale_caches();
+            }
+        }
+    }
+
+    /// Register a template from a path, which is reloaded automatically. Caches rendered from the template it replaces, or from templates using it as a partial, are dropped.
+    #[inline]
+    pub fn register_template_file<S: Into<String>, P: Into<PathBuf>>(
+        &self,
+        name: S,
+        file_path: P,
+    ) -> Result<(), Box<TemplateError>> {
+        let mut handlebars = self.handlebars.lock().unwrap_or_else(PoisonError::into_inner);
+
+        handlebars.register_template_file(name, file_path)?;
+
+        #[cfg(feature = "debug_cache")]
+        self.invalidate_cache_if_reloaded(&handlebars);
+
+        Ok(())
+    }
+
+    /// Register a template from a string. Caches rendered from the template it replaces, or from templates using it as a partial, are dropped.
+    #[inline]
+    pub fn register_template_string<S: Into<String>, T: AsRef<str>>(
+        &self,
+        name: S,
+        tpl_str: T,
+    ) -> Result<(), Box<TemplateError>> {
+        let mut handlebars = self.handlebars.lock().unwrap_or_else(PoisonError::into_inner);
+
+        handlebars.register_template_string(name, tpl_str)?;
+
+        #[cfg(feature = "debug_cache")]
+        self.invalidate_cache_if_reloaded(&handlebars);
+
+        Ok(())
+    }
+
+    /// Unregister a template. Caches rendered from the template, or from templates using it as a partial, are dropped.
+    #[inline]
+    pub fn unregister_template<S: AsRef<str>>(&self, name: S) {
+        let mut handlebars = self.handlebars.lock().unwrap_or_else(PoisonError::into_inner);
+
+        handlebars.unregister_template(name);
+
+        #[cfg(feature = "debug_cache")]
+        self.invalidate_cache_if_reloaded(&handlebars);
+    }
+
     /// Build a `HandlebarsResponse`.
     #[inline]
     pub fn build<S: AsRef<str>, V: Serialize>(
@@ -32,10 +176,12 @@ impl HandlebarsContextManager {
         name: S,
         context: V,
     ) -> HandlebarsResponse {
+        let name = name.as_ref();
+
         self.handlebars
             .lock()
             .unwrap_or_else(PoisonError::into_inner)
-            .render(name.as_ref(), &context)
+            .render(name, &context)
             .map(|html| {
                 let etag = compute_data_etag(html.as_bytes());
 
@@ -44,12 +190,74 @@ impl HandlebarsContextManager {
                 } else {
                     let html = if minify { html_minifier::minify(html).unwrap() } else { html };
 
-                    HandlebarsResponse::build_not_cache(html, &etag)
+                    let res = HandlebarsResponse::build_not_cache(html, &etag);
+
+                    #[cfg(feature = "debug_cache")]
+                    let res = res.with_templates(self.template_versions().get(name));
+
+                    res
                 }
             })
             .unwrap()
     }
 
+    /// Resolve the variant of a template for a locale. For `de-AT`, `terms` is resolved to `terms.de-AT`, then `terms.de`, and then `terms` itself, whichever is registered first.
+    #[inline]
+    pub fn resolve_template<'a>(&self, locale: &Locale, name: &'a str) -> Cow<'a, str> {
+        let handlebars = self.handlebars.lock().unwrap_or_else(PoisonError::into_inner);
+
+        for tag in locale.fallbacks() {
+            let variant = format!("{}.{}", name, tag);
+
+            if handlebars.has_template(&variant) {
+                return Cow::Owned(variant);
+            }
+        }
+
+        Cow::Borrowed(name)
+    }
+
+    /// Build a `HandlebarsResponse` in a locale, which is used by the `t` and `locale` helpers of the `i18n` feature, from the variant of the template resolved by `resolve_template`. To cache the response, use `handlebars_response_cache!(localized ...)`, which keys the cache by `localized_cache_key`.
+    #[inline]
+    pub fn build_localized<S: AsRef<str>, V: Serialize>(
+        &self,
+        etag_if_none_match: &EtagIfNoneMatch<'_>,
+        minify: bool,
+        locale: &Locale,
+        name: S,
+        context: V,
+    ) -> HandlebarsResponse {
+        let name = self.resolve_template(locale, name.as_ref());
+
+        locale.scope(|| self.build(etag_if_none_match, minify, name, context))
+    }
+
+    /// Get the key of the cache of a template built by `build_localized`, which is `key` followed by `@`, the language tag of the variant of the template resolved by `resolve_template`, and, with the `i18n` feature, the locale negotiated by the translations registered by `handlebars_translations_initialize!`, like `terms@de-AT`. If there is no variant and no negotiated locale, the key is kept as it is.
+    #[cfg(feature = "debug_cache")]
+    #[inline]
+    pub fn localized_cache_key<S: AsRef<str>, K: Into<String>>(
+        &self,
+        locale: &Locale,
+        name: S,
+        key: K,
+    ) -> String {
+        let name = self.resolve_template(locale, name.as_ref());
+
+        let mut key = key.into();
+
+        #[cfg(feature = "i18n")]
+        let suffix = locale.cache_key_suffix(&name, self.translations.as_deref());
+        #[cfg(not(feature = "i18n"))]
+        let suffix = locale.cache_key_suffix(&name);
+
+        if let Some(suffix) = suffix {
+            key.push('@');
+            key.push_str(&suffix);
+        }
+
+        key
+    }
+
     /// Render a template.
     #[inline]
     pub fn render<S: AsRef<str>, V: Serialize>(&self, name: S, context: V) -> String {
@@ -59,4 +267,17 @@ impl HandlebarsContextManager {
             .render(name.as_ref(), &context)
             .unwrap()
     }
+
+    /// Render the variant of a template in a locale.
+    #[inline]
+    pub fn render_localized<S: AsRef<str>, V: Serialize>(
+        &self,
+        locale: &Locale,
+        name: S,
+        context: V,
+    ) -> String {
+        let name = self.resolve_template(locale, name.as_ref());
+
+        locale.scope(|| self.render(name, context))
+    }
 }
@@ -13,7 +13,8 @@ use crate::functions::add_helpers;
 /// Reloadable Handlebars.
 pub struct ReloadableHandlebars {
     handlebars: Handlebars<'static>,
-    files:      HashMap<&'static str, (PathBuf, Option<SystemTime>)>,
+    files:      HashMap<String, (PathBuf, Option<SystemTime>)>,
+    generation: u64,
 }
 
 impl ReloadableHandlebars {
@@ -27,30 +28,64 @@ impl ReloadableHandlebars {
         ReloadableHandlebars {
             handlebars,
             files: HashMap::new(),
+            generation: 0,
         }
     }
 
     /// Register a template from a path and it can be reloaded automatically.
     #[inline]
-    pub fn register_template_file<P: Into<PathBuf>>(
+    pub fn register_template_file<S: Into<String>, P: Into<PathBuf>>(
         &mut self,
-        name: &'static str,
+        name: S,
         file_path: P,
     ) -> Result<(), Box<TemplateError>> {
+        let name = name.into();
         let file_path = file_path.into();
 
         let metadata =
-            file_path.metadata().map_err(|err| TemplateError::from((err, String::from(name))))?;
+            file_path.metadata().map_err(|err| TemplateError::from((err, name.clone())))?;
 
         let mtime = metadata.modified().ok();
 
-        self.handlebars.register_template_file(name, &file_path)?;
+        self.handlebars.register_template_file(&name, &file_path)?;
 
         self.files.insert(name, (file_path, mtime));
 
+        self.generation += 1;
+
+        Ok(())
+    }
+
+    /// Register a template from a string. It replaces the template from a file with the same name, if any.
+    #[inline]
+    pub fn register_template_string<S: Into<String>, T: AsRef<str>>(
+        &mut self,
+        name: S,
+        tpl_str: T,
+    ) -> Result<(), Box<TemplateError>> {
+        let name = name.into();
+
+        self.handlebars.register_template_string(&name, tpl_str)?;
+
+        self.files.remove(&name);
+
+        self.generation += 1;
+
         Ok(())
     }
 
+    /// Unregister a template by a name, whether it is from a file or not.
+    #[inline]
+    pub fn unregister_template<S: AsRef<str>>(&mut self, name: S) {
+        let name = name.as_ref();
+
+        self.files.remove(name);
+
+        self.handlebars.unregister_template(name);
+
+        self.generation += 1;
+    }
+
     /// Unregister a template from a file by a name.
     #[inline]
     pub fn unregister_template_file<S: AsRef<str>>(&mut self, name: S) -> Option<PathBuf> {
@@ -60,6 +95,8 @@ impl ReloadableHandlebars {
             Some((file_path, _)) => {
                 self.handlebars.unregister_template(name);
 
+                self.generation += 1;
+
                 Some(file_path)
             },
             None => None,
@@ -69,10 +106,9 @@ impl ReloadableHandlebars {
     /// Reload templates if needed.
     #[inline]
     pub fn reload_if_needed(&mut self) -> Result<(), Box<TemplateError>> {
-        for (&name, (file_path, mtime)) in &mut self.files {
-            let metadata = file_path
-                .metadata()
-                .map_err(|err| TemplateError::from((err, String::from(name))))?;
+        for (name, (file_path, mtime)) in &mut self.files {
+            let metadata =
+                file_path.metadata().map_err(|err| TemplateError::from((err, name.clone())))?;
 
             let (reload, new_mtime) = match mtime {
                 Some(mtime) => match metadata.modified() {
@@ -89,11 +125,19 @@ impl ReloadableHandlebars {
                 self.handlebars.register_template_file(name, &file_path)?;
 
                 *mtime = new_mtime;
+
+                self.generation += 1;
             }
         }
 
         Ok(())
     }
+
+    /// Get the number of times templates have been registered, reloaded or unregistered through this `ReloadableHandlebars`.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
 }
 
 impl Default for ReloadableHandlebars {
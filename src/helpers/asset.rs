@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, PoisonError, RwLock},
+    time::SystemTime,
+};
+
+use handlebars::{
+    html_escape, Context, Handlebars, Helper, HelperDef, HelperResult, JsonValue, Output,
+    RenderContext, RenderError, ScopedJson,
+};
+
+/// A file in an asset manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Asset {
+    /// The fingerprinted path of the file, like `assets/app.3f9a1c.js`.
+    pub file:      String,
+    /// The SRI hash of the file, like `sha384-...`, if the manifest has one.
+    pub integrity: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    assets: HashMap<String, Asset>,
+    mtime:  Option<SystemTime>,
+}
+
+/// An asset manifest emitted by a frontend build tool, which maps the names of assets to their fingerprinted files.
+///
+/// Both the flat format of `webpack-manifest-plugin`, like `{"app.js": "app.3f9a1c.js"}`, and the format of Vite, like `{"src/main.ts": {"file": "assets/main.4889e940.js"}}`, are supported. An `integrity` field in an entry is used as its SRI hash.
+#[derive(Debug)]
+pub struct AssetManifest {
+    base:    String,
+    path:    Option<PathBuf>,
+    entries: RwLock<Entries>,
+}
+
+impl AssetManifest {
+    /// Parse a manifest. The URLs of the assets are `base` joined with their files.
+    #[inline]
+    pub fn from_json<S: Into<String>>(base: S, json: &str) -> Result<AssetManifest, io::Error> {
+        Ok(AssetManifest {
+            base:    base.into(),
+            path:    None,
+            entries: RwLock::new(Entries {
+                assets: parse(json)?, mtime: None
+            }),
+        })
+    }
+
+    /// Read a manifest from a file. The file is read again when it is modified.
+    #[inline]
+    pub fn from_file<S: Into<String>, P: Into<PathBuf>>(
+        base: S,
+        path: P,
+    ) -> Result<AssetManifest, io::Error> {
+        let path = path.into();
+
+        let entries = read(&path)?;
+
+        Ok(AssetManifest {
+            base:    base.into(),
+            path:    Some(path),
+            entries: RwLock::new(entries),
+        })
+    }
+
+    /// Read the manifest again if it is read from a file which has been modified.
+    #[inline]
+    pub fn reload_if_needed(&self) -> Result<(), io::Error> {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let mtime = path.metadata()?.modified().ok();
+
+        let modified = {
+            let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+
+            match (entries.mtime, mtime) {
+                (Some(old), Some(new)) => new > old,
+                _ => true,
+            }
+        };
+
+        if modified {
+            *self.entries.write().unwrap_or_else(PoisonError::into_inner) = read(path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get an asset by its name.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<Asset> {
+        self.entries.read().unwrap_or_else(PoisonError::into_inner).assets.get(name).cloned()
+    }
+
+    /// Get the URL of a file.
+    #[inline]
+    pub fn url(&self, asset: &Asset) -> String {
+        format!("{}/{}", self.base.trim_end_matches('/'), asset.file.trim_start_matches('/'))
+    }
+
+    #[inline]
+    fn lookup(&self, helper_name: &str, h: &Helper) -> Result<Asset, RenderError> {
+        let name = h.param(0).and_then(|param| param.value().as_str()).ok_or_else(|| {
+            RenderError::new(format!(
+                "The `{}` helper needs the name of an asset as its first parameter.",
+                helper_name
+            ))
+        })?;
+
+        self.reload_if_needed().map_err(|err| {
+            RenderError::new(format!("The asset manifest cannot be read: {}", err))
+        })?;
+
+        self.get(name).ok_or_else(|| {
+            RenderError::new(format!("The asset `{}` is not in the asset manifest.", name))
+        })
+    }
+}
+
+#[inline]
+fn read(path: &Path) -> Result<Entries, io::Error> {
+    let mtime = path.metadata()?.modified().ok();
+
+    let assets = parse(&std::fs::read_to_string(path)?)?;
+
+    Ok(Entries {
+        assets,
+        mtime,
+    })
+}
+
+fn parse(json: &str) -> Result<HashMap<String, Asset>, io::Error> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let object = match json.parse()? {
+        JsonValue::Object(object) => object,
+        _ => return Err(invalid(String::from("An asset manifest needs to be an object."))),
+    };
+
+    let mut assets = HashMap::with_capacity(object.len());
+
+    for (name, entry) in object {
+        let asset = match entry {
+            JsonValue::String(file) => Asset {
+                file,
+                integrity: None,
+            },
+            JsonValue::Object(mut entry) => match entry.remove("file") {
+                Some(JsonValue::String(file)) => Asset {
+                    file,
+                    integrity: match entry.remove("integrity") {
+                        Some(JsonValue::String(integrity)) => Some(integrity),
+                        _ => None,
+                    },
+                },
+                _ => {
+                    return Err(invalid(format!(
+                        "The entry `{}` of the asset manifest has no `file`.",
+                        name
+                    )))
+                },
+            },
+            _ => {
+                return Err(invalid(format!(
+                    "The entry `{}` of the asset manifest needs to be a string or an object.",
+                    name
+                )))
+            },
+        };
+
+        assets.insert(name, asset);
+    }
+
+    Ok(assets)
+}
+
+/// Register `{{asset "app.js"}}`, `{{asset_integrity "app.js"}}` and `{{asset_preload "app.js"}}` with a manifest. Use the `handlebars_asset_manifest!` macro in the fairing instead of calling this function directly, so that the manifest is embedded in release builds and reloaded in debug builds.
+///
+/// * `asset` returns the URL of an asset, like `/static/app.3f9a1c.js`.
+/// * `asset_integrity` returns the SRI hash of an asset from the manifest, or an empty string if there is none.
+/// * `asset_preload` writes a `<link rel="preload">` element for an asset, with its `integrity` attribute if the manifest has one. The `as` attribute is guessed from the extension, and can be set by `as="..."`.
+///
+/// Unknown assets are errors.
+pub fn register_asset_helpers(handlebars: &mut Handlebars, manifest: AssetManifest) {
+    let manifest = Arc::new(manifest);
+
+    handlebars.register_helper(
+        "asset",
+        Box::new(AssetHelper {
+            manifest: manifest.clone(), integrity: false
+        }),
+    );
+
+    handlebars.register_helper(
+        "asset_integrity",
+        Box::new(AssetHelper {
+            manifest: manifest.clone(), integrity: true
+        }),
+    );
+
+    handlebars.register_helper(
+        "asset_preload",
+        Box::new(AssetPreloadHelper {
+            manifest,
+        }),
+    );
+}
+
+struct AssetHelper {
+    manifest:  Arc<AssetManifest>,
+    integrity: bool,
+}
+
+impl HelperDef for AssetHelper {
+    #[inline]
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let name = if self.integrity { "asset_integrity" } else { "asset" };
+
+        let asset = self.manifest.lookup(name, h)?;
+
+        let value = if self.integrity {
+            asset.integrity.unwrap_or_default()
+        } else {
+            self.manifest.url(&asset)
+        };
+
+        Ok(ScopedJson::Derived(JsonValue::String(value)))
+    }
+}
+
+struct AssetPreloadHelper {
+    manifest: Arc<AssetManifest>,
+}
+
+impl HelperDef for AssetPreloadHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let asset = self.manifest.lookup("asset_preload", h)?;
+
+        let destination = match h.hash_get("as").and_then(|value| value.value().as_str()) {
+            Some(destination) => destination,
+            None => destination(&asset.file),
+        };
+
+        out.write("<link rel=\"preload\" href=\"")?;
+        out.write(&html_escape(&self.manifest.url(&asset)))?;
+        out.write("\" as=\"")?;
+        out.write(&html_escape(destination))?;
+        out.write("\"")?;
+
+        if let Some(integrity) = asset.integrity.as_ref() {
+            out.write(" integrity=\"")?;
+            out.write(&html_escape(integrity))?;
+            out.write("\"")?;
+        }
+
+        // fonts are always fetched in CORS mode, and SRI needs CORS
+        if asset.integrity.is_some() || destination == "font" {
+            out.write(" crossorigin=\"anonymous\"")?;
+        }
+
+        out.write(">")?;
+
+        Ok(())
+    }
+}
+
+/// Guess the `as` attribute of a preload link from the extension of a file.
+#[inline]
+fn destination(file: &str) -> &'static str {
+    let extension = file.rsplit_once('.').map(|(_, extension)| extension).unwrap_or("");
+
+    match extension.to_ascii_lowercase().as_str() {
+        "js" | "mjs" => "script",
+        "css" => "style",
+        "woff" | "woff2" | "ttf" | "otf" | "eot" => "font",
+        "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "avif" | "ico" => "image",
+        _ => "fetch",
+    }
+}
@@ -0,0 +1,387 @@
+use std::{cmp::Ordering, mem};
+
+use handlebars::{Handlebars, Helper, JsonValue, RenderError};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{compare::json_eq, hash_str, param, register_value_helper, type_name};
+
+/// The maximum number of items `range` can generate.
+const MAX_RANGE_LEN: u64 = 100_000;
+
+/// `{{len value}}`, `{{first array}}`, `{{last array}}`, `{{slice array start end}}`, `{{sort_by array "field" desc=false}}`, `{{group_by array "field"}}`, `{{range start end step}}`, `{{contains collection value}}`, `{{keys object}}` and `{{values object}}`.
+///
+/// * `len` counts the items of an array, the entries of an object or the grapheme clusters of a string. `null` and missing values have no length. It replaces the built-in `len` of Handlebars, which counts the bytes of a string and gives `0` for any other value, so `{{len "日本"}}` becomes `2` instead of `6`, and counting a number or a boolean is an error.
+/// * `first` and `last` return an item, or `null` if the array is empty. With a count, like `{{first array 3}}`, they return an array of at most that many items.
+/// * `slice` returns the items from `start` (inclusive) to `end` (exclusive, the end of the array by default). Negative indices count from the end.
+/// * `sort_by` sorts the items by the value of `field`, which can be a dotted path like `"author.name"`. Without a field, the items themselves are compared. The sort is stable and items without the field come last. The values need to be all numbers, all strings or all booleans.
+/// * `group_by` returns an array of `{"key": ..., "items": [...]}` objects, in the order in which the keys first appear, so that `{{#each (group_by posts "year")}}{{key}}{{#each items}}...{{/each}}{{/each}}` works.
+/// * `range` returns the integers from `start` (inclusive) to `end` (exclusive). `step` is `1` by default and can be negative.
+/// * `contains` checks if an array has an item, an object has a key or a string has a substring, like `in` with the parameters swapped.
+/// * `keys` and `values` return the keys and values of an object in the order of the map of `serde_json`, which is sorted by key unless its `preserve_order` feature is enabled.
+pub(super) fn register(handlebars: &mut Handlebars) {
+    register_value_helper(handlebars, "len", |name, h| {
+        let len = match param(name, h, 0)? {
+            JsonValue::Array(array) => array.len(),
+            JsonValue::Object(object) => object.len(),
+            JsonValue::String(s) => s.graphemes(true).count(),
+            JsonValue::Null => 0,
+            value => {
+                return Err(RenderError::new(format!(
+                    "The `{}` helper cannot count {}.",
+                    name,
+                    type_name(value)
+                )))
+            },
+        };
+
+        Ok(JsonValue::from(len))
+    });
+
+    register_value_helper(handlebars, "first", |name, h| {
+        let array = array(name, param(name, h, 0)?)?;
+
+        match count(name, h)? {
+            Some(count) => Ok(JsonValue::Array(array.iter().take(count).cloned().collect())),
+            None => Ok(array.first().cloned().unwrap_or(JsonValue::Null)),
+        }
+    });
+
+    register_value_helper(handlebars, "last", |name, h| {
+        let array = array(name, param(name, h, 0)?)?;
+
+        match count(name, h)? {
+            Some(count) => {
+                Ok(JsonValue::Array(array[array.len().saturating_sub(count)..].to_vec()))
+            },
+            None => Ok(array.last().cloned().unwrap_or(JsonValue::Null)),
+        }
+    });
+
+    register_value_helper(handlebars, "slice", |name, h| {
+        let array = array(name, param(name, h, 0)?)?;
+        let start = index(name, param(name, h, 1)?, array.len())?;
+
+        let end = match h.param(2).map(|end| end.value()) {
+            Some(JsonValue::Null) | None => array.len(),
+            Some(end) => index(name, end, array.len())?,
+        };
+
+        Ok(JsonValue::Array(if start < end { array[start..end].to_vec() } else { Vec::new() }))
+    });
+
+    register_value_helper(handlebars, "sort_by", |name, h| {
+        let array = array(name, param(name, h, 0)?)?;
+        let field = field(name, h)?;
+
+        let desc = match h.hash_get("desc").map(|desc| desc.value()) {
+            Some(JsonValue::Bool(desc)) => *desc,
+            Some(JsonValue::Null) | None => false,
+            Some(desc) => {
+                return Err(RenderError::new(format!(
+                    "The `desc` argument of the `{}` helper needs to be a boolean, but it is {}.",
+                    name,
+                    type_name(desc)
+                )))
+            },
+        };
+
+        // the keys are checked before sorting, so that they can be compared with a total order
+        let mut keyed = Vec::with_capacity(array.len());
+        let mut kind: Option<(&JsonValue, mem::Discriminant<SortKey>)> = None;
+
+        for item in array {
+            let key = match lookup(item, field) {
+                Some(value) => {
+                    let key = SortKey::new(value).ok_or_else(|| {
+                        RenderError::new(format!(
+                            "The `{}` helper cannot sort by {}.",
+                            name,
+                            type_name(value)
+                        ))
+                    })?;
+
+                    let (first, first_kind) = *kind.get_or_insert((value, mem::discriminant(&key)));
+
+                    if first_kind != mem::discriminant(&key) {
+                        return Err(RenderError::new(format!(
+                            "The `{}` helper cannot compare {} with {}.",
+                            name,
+                            type_name(first),
+                            type_name(value)
+                        )));
+                    }
+
+                    Some(key)
+                },
+                None => None,
+            };
+
+            keyed.push((key, item));
+        }
+
+        keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) => {
+                if desc {
+                    b.total_cmp(a)
+                } else {
+                    a.total_cmp(b)
+                }
+            },
+            // items without the field always come last
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+
+        Ok(JsonValue::Array(keyed.into_iter().map(|(_, item)| item.clone()).collect()))
+    });
+
+    register_value_helper(handlebars, "group_by", |name, h| {
+        let array = array(name, param(name, h, 0)?)?;
+        let field = field(name, h)?;
+
+        let mut groups: Vec<(JsonValue, Vec<JsonValue>)> = Vec::new();
+
+        for item in array {
+            let key = lookup(item, field).unwrap_or(&JsonValue::Null);
+
+            match groups.iter_mut().find(|(k, _)| json_eq(k, key)) {
+                Some((_, items)) => items.push(item.clone()),
+                None => groups.push((key.clone(), vec![item.clone()])),
+            }
+        }
+
+        let groups = groups
+            .into_iter()
+            .map(|(key, items)| {
+                JsonValue::from_iter([("key", key), ("items", JsonValue::Array(items))])
+            })
+            .collect();
+
+        Ok(JsonValue::Array(groups))
+    });
+
+    register_value_helper(handlebars, "range", |name, h| {
+        let start = integer(name, param(name, h, 0)?)?;
+        let end = integer(name, param(name, h, 1)?)?;
+
+        let step = match h.param(2).map(|step| step.value()) {
+            Some(JsonValue::Null) | None => 1,
+            Some(step) => integer(name, step)?,
+        };
+
+        if step == 0 {
+            return Err(RenderError::new(format!(
+                "The step of the `{}` helper cannot be 0.",
+                name
+            )));
+        }
+
+        let span = if step > 0 { end.saturating_sub(start) } else { start.saturating_sub(end) };
+        let len = if span > 0 { (span as u64 - 1) / step.unsigned_abs() + 1 } else { 0 };
+
+        if len > MAX_RANGE_LEN {
+            return Err(RenderError::new(format!(
+                "The `{}` helper cannot generate more than {} numbers.",
+                name, MAX_RANGE_LEN
+            )));
+        }
+
+        let numbers = (0..len as i64).map(|i| JsonValue::from(start + i * step)).collect();
+
+        Ok(JsonValue::Array(numbers))
+    });
+
+    register_value_helper(handlebars, "contains", |name, h| {
+        let value = param(name, h, 1)?;
+
+        let contained = match param(name, h, 0)? {
+            JsonValue::Array(array) => array.iter().any(|v| json_eq(value, v)),
+            JsonValue::Object(object) => {
+                value.as_str().map(|key| object.contains_key(key)).unwrap_or(false)
+            },
+            JsonValue::String(s) => value.as_str().map(|sub| s.contains(sub)).unwrap_or(false),
+            JsonValue::Null => false,
+            collection => {
+                return Err(RenderError::new(format!(
+                    "The `{}` helper cannot look for a value in {}.",
+                    name,
+                    type_name(collection)
+                )))
+            },
+        };
+
+        Ok(JsonValue::Bool(contained))
+    });
+
+    register_value_helper(handlebars, "keys", |name, h| {
+        let keys = entries(name, param(name, h, 0)?)?
+            .map(|(key, _)| JsonValue::String(key.clone()))
+            .collect();
+
+        Ok(JsonValue::Array(keys))
+    });
+
+    register_value_helper(handlebars, "values", |name, h| {
+        let values = entries(name, param(name, h, 0)?)?.map(|(_, value)| value.clone()).collect();
+
+        Ok(JsonValue::Array(values))
+    });
+}
+
+/// The value of an item which `sort_by` sorts by.
+enum SortKey<'a> {
+    /// A number, with its exact value if it is an integer, so that integers which cannot be represented exactly by `f64` are still ordered.
+    Number(f64, i128),
+    String(&'a str),
+    Bool(bool),
+}
+
+impl<'a> SortKey<'a> {
+    #[inline]
+    fn new(value: &'a JsonValue) -> Option<SortKey<'a>> {
+        match value {
+            JsonValue::Number(n) => {
+                let f = n.as_f64()?;
+
+                let exact = match (n.as_i64(), n.as_u64()) {
+                    (Some(i), _) => i128::from(i),
+                    (None, Some(u)) => i128::from(u),
+                    // a float as large as an inexact integer is an integer itself
+                    (None, None) => f as i128,
+                };
+
+                Some(SortKey::Number(f, exact))
+            },
+            JsonValue::String(s) => Some(SortKey::String(s)),
+            JsonValue::Bool(b) => Some(SortKey::Bool(*b)),
+            _ => None,
+        }
+    }
+
+    /// Compare two keys of the same kind. Keys of different kinds are ordered by their kinds, which `sort_by` does not let happen.
+    #[inline]
+    fn total_cmp(&self, other: &SortKey) -> Ordering {
+        match (self, other) {
+            (SortKey::Number(a, i), SortKey::Number(b, j)) => a.total_cmp(b).then_with(|| i.cmp(j)),
+            (SortKey::String(a), SortKey::String(b)) => a.cmp(b),
+            (SortKey::Bool(a), SortKey::Bool(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    #[inline]
+    fn rank(&self) -> u8 {
+        match self {
+            SortKey::Number(..) => 0,
+            SortKey::String(_) => 1,
+            SortKey::Bool(_) => 2,
+        }
+    }
+}
+
+/// Get an array. `null` is treated as an empty array.
+#[inline]
+fn array<'a>(name: &str, value: &'a JsonValue) -> Result<&'a [JsonValue], RenderError> {
+    match value {
+        JsonValue::Array(array) => Ok(array),
+        JsonValue::Null => Ok(&[]),
+        _ => Err(RenderError::new(format!(
+            "The `{}` helper needs an array, but got {}.",
+            name,
+            type_name(value)
+        ))),
+    }
+}
+
+/// Get the entries of an object. `null` is treated as an empty object.
+#[inline]
+fn entries<'a>(
+    name: &str,
+    value: &'a JsonValue,
+) -> Result<impl Iterator<Item = (&'a String, &'a JsonValue)>, RenderError> {
+    match value {
+        JsonValue::Object(_) | JsonValue::Null => Ok(value.as_object().into_iter().flatten()),
+        _ => Err(RenderError::new(format!(
+            "The `{}` helper needs an object, but got {}.",
+            name,
+            type_name(value)
+        ))),
+    }
+}
+
+#[inline]
+fn integer(name: &str, value: &JsonValue) -> Result<i64, RenderError> {
+    value.as_i64().ok_or_else(|| {
+        RenderError::new(format!(
+            "The `{}` helper needs integers, but got {}.",
+            name,
+            type_name(value)
+        ))
+    })
+}
+
+/// Resolve a possibly negative index against the length of an array.
+#[inline]
+fn index(name: &str, value: &JsonValue, len: usize) -> Result<usize, RenderError> {
+    let index = integer(name, value)?;
+
+    if index < 0 {
+        Ok(len.saturating_sub(index.unsigned_abs() as usize))
+    } else {
+        Ok((index as usize).min(len))
+    }
+}
+
+/// Get the optional count of `first` and `last`.
+#[inline]
+fn count(name: &str, h: &Helper) -> Result<Option<usize>, RenderError> {
+    match h.param(1).map(|count| count.value()) {
+        Some(JsonValue::Null) | None => Ok(None),
+        Some(count) => count.as_u64().map(|count| Some(count as usize)).ok_or_else(|| {
+            RenderError::new(format!(
+                "The count of the `{}` helper needs to be a non-negative integer.",
+                name
+            ))
+        }),
+    }
+}
+
+/// Get the field of `sort_by` and `group_by`, which can also be given as the `field` argument.
+#[inline]
+fn field<'a>(name: &str, h: &'a Helper) -> Result<Option<&'a str>, RenderError> {
+    match h.param(1).map(|field| field.value()) {
+        Some(JsonValue::String(field)) => Ok(Some(field)),
+        Some(JsonValue::Null) | None => hash_str(name, h, "field"),
+        Some(field) => Err(RenderError::new(format!(
+            "The field of the `{}` helper needs to be a string, but it is {}.",
+            name,
+            type_name(field)
+        ))),
+    }
+}
+
+/// Look up a dotted path in a value. Without a path, the value itself is returned.
+#[inline]
+fn lookup<'a>(value: &'a JsonValue, path: Option<&str>) -> Option<&'a JsonValue> {
+    let path = match path {
+        Some(path) => path,
+        None => return Some(value),
+    };
+
+    let mut value = value;
+
+    for segment in path.split('.') {
+        value = match value {
+            JsonValue::Object(object) => object.get(segment)?,
+            JsonValue::Array(array) => array.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+
+    if value.is_null() {
+        None
+    } else {
+        Some(value)
+    }
+}
@@ -0,0 +1,118 @@
+use std::cmp::Ordering;
+
+use handlebars::{Handlebars, Helper, JsonValue, RenderError};
+
+use super::{param, register_value_helper, type_name};
+
+/// `{{eq a b}}`, `{{ne a b}}`, `{{gt a b}}`, `{{lt a b}}`, `{{gte a b}}`, `{{lte a b}}` and `{{in value collection}}`.
+///
+/// Values are compared without type coercion, so `{{eq 1 "1"}}` is `false`, but integers and floats are compared by their numeric values. `eq` and `ne` work on any JSON values, including arrays and objects. The ordering helpers only work on two numbers, two strings or two booleans, and fail otherwise. They replace the built-in helpers of Handlebars with the same names, so the `helpers` feature does not enable them.
+pub(super) fn register(handlebars: &mut Handlebars) {
+    register_value_helper(handlebars, "eq", |name, h| {
+        Ok(JsonValue::Bool(json_eq(param(name, h, 0)?, param(name, h, 1)?)))
+    });
+
+    register_value_helper(handlebars, "ne", |name, h| {
+        Ok(JsonValue::Bool(!json_eq(param(name, h, 0)?, param(name, h, 1)?)))
+    });
+
+    register_value_helper(handlebars, "gt", |name, h| {
+        Ok(JsonValue::Bool(compare(name, h)? == Ordering::Greater))
+    });
+
+    register_value_helper(handlebars, "lt", |name, h| {
+        Ok(JsonValue::Bool(compare(name, h)? == Ordering::Less))
+    });
+
+    register_value_helper(handlebars, "gte", |name, h| {
+        Ok(JsonValue::Bool(compare(name, h)? != Ordering::Less))
+    });
+
+    register_value_helper(handlebars, "lte", |name, h| {
+        Ok(JsonValue::Bool(compare(name, h)? != Ordering::Greater))
+    });
+
+    register_value_helper(handlebars, "in", |name, h| {
+        let value = param(name, h, 0)?;
+
+        let contained = match param(name, h, 1)? {
+            JsonValue::Array(array) => array.iter().any(|v| json_eq(value, v)),
+            JsonValue::Object(object) => {
+                value.as_str().map(|key| object.contains_key(key)).unwrap_or(false)
+            },
+            JsonValue::String(s) => value.as_str().map(|sub| s.contains(sub)).unwrap_or(false),
+            JsonValue::Null => false,
+            collection => {
+                return Err(RenderError::new(format!(
+                    "The `{}` helper cannot look for a value in {}.",
+                    name,
+                    type_name(collection)
+                )))
+            },
+        };
+
+        Ok(JsonValue::Bool(contained))
+    });
+}
+
+#[inline]
+fn compare(name: &str, h: &Helper) -> Result<Ordering, RenderError> {
+    let a = param(name, h, 0)?;
+    let b = param(name, h, 1)?;
+
+    json_cmp(a, b).ok_or_else(|| {
+        RenderError::new(format!(
+            "The `{}` helper cannot compare {} with {}.",
+            name,
+            type_name(a),
+            type_name(b)
+        ))
+    })
+}
+
+/// Compare two numbers by their values, regardless of whether they are stored as integers or floats.
+pub(super) fn number_cmp(a: &JsonValue, b: &JsonValue) -> Option<Ordering> {
+    if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+        return Some(a.cmp(&b));
+    }
+
+    if let (Some(a), Some(b)) = (a.as_u64(), b.as_u64()) {
+        return Some(a.cmp(&b));
+    }
+
+    // a `u64` which does not fit in `i64` is greater than any negative `i64`
+    if a.is_u64() && b.is_i64() {
+        return Some(Ordering::Greater);
+    }
+
+    if a.is_i64() && b.is_u64() {
+        return Some(Ordering::Less);
+    }
+
+    a.as_f64()?.partial_cmp(&b.as_f64()?)
+}
+
+/// Check if two JSON values are equal. Numbers are compared by their values.
+pub(super) fn json_eq(a: &JsonValue, b: &JsonValue) -> bool {
+    match (a, b) {
+        (JsonValue::Number(_), JsonValue::Number(_)) => number_cmp(a, b) == Some(Ordering::Equal),
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| json_eq(a, b))
+        },
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            a.len() == b.len()
+                && a.iter().all(|(key, a)| b.get(key).map(|b| json_eq(a, b)).unwrap_or(false))
+        },
+        _ => a == b,
+    }
+}
+
+/// Compare two numbers, two strings or two booleans. Return `None` for other values.
+pub(super) fn json_cmp(a: &JsonValue, b: &JsonValue) -> Option<Ordering> {
+    match (a, b) {
+        (JsonValue::Number(_), JsonValue::Number(_)) => number_cmp(a, b),
+        (JsonValue::String(a), JsonValue::String(b)) => Some(a.cmp(b)),
+        (JsonValue::Bool(a), JsonValue::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
@@ -0,0 +1,243 @@
+use std::{fmt::Write, str::FromStr, sync::Arc};
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, FixedOffset, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, JsonValue, RenderContext, RenderError, ScopedJson,
+};
+
+use super::{hash_str, param, register_value_helper, type_name};
+
+/// The default format of `format_date`, which is RFC 3339.
+const DEFAULT_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+type Now = Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>;
+
+/// Register the date helpers with a custom source of the current time, which makes `time_ago` and `now` deterministic in tests.
+///
+/// `{{format_date value "%Y-%m-%d" tz="Asia/Taipei"}}`, `{{time_ago value}}`, `{{to_timezone value "Europe/Berlin"}}`, `{{timestamp value}}` and `{{now}}`.
+///
+/// A date can be an RFC 3339 or ISO 8601 string, like `2024-05-01T12:30:00+08:00`, `2024-05-01T12:30:00Z`, `2024-05-01T12:30:00` or `2024-05-01`, or a Unix timestamp in seconds. Dates without an offset are in UTC.
+///
+/// * `format_date` formats a date with the `strftime`-like syntax of `chrono` (RFC 3339 by default). Without `tz`, the offset of the date is kept.
+/// * `time_ago` describes a date relative to now in English, like `just now`, `5 minutes ago` or `in 2 days`.
+/// * `to_timezone` converts a date to a time zone and returns it as an RFC 3339 string. A time zone is an IANA name, like `Europe/Berlin` or `UTC`, or a fixed offset, like `+08:00`.
+/// * `timestamp` returns the Unix timestamp of a date in seconds.
+/// * `now` returns the current time as an RFC 3339 string, so that it can be passed to the other helpers.
+pub fn register_date_helpers<F: Fn() -> DateTime<Utc> + Send + Sync + 'static>(
+    handlebars: &mut Handlebars,
+    now: F,
+) {
+    let now: Now = Arc::new(now);
+
+    register_value_helper(handlebars, "format_date", |name, h| {
+        let date = date(name, param(name, h, 0)?)?;
+
+        let format = match h.param(1).map(|format| format.value()) {
+            Some(JsonValue::String(format)) => format.as_str(),
+            Some(JsonValue::Null) | None => DEFAULT_FORMAT,
+            Some(format) => {
+                return Err(RenderError::new(format!(
+                    "The format of the `{}` helper needs to be a string, but it is {}.",
+                    name,
+                    type_name(format)
+                )))
+            },
+        };
+
+        let formatted = match hash_str(name, h, "tz")? {
+            Some(tz) => match zone(name, tz)? {
+                Zone::Named(tz) => format_date(name, date.with_timezone(&tz), format)?,
+                Zone::Fixed(offset) => format_date(name, date.with_timezone(&offset), format)?,
+            },
+            None => format_date(name, date, format)?,
+        };
+
+        Ok(JsonValue::String(formatted))
+    });
+
+    register_value_helper(handlebars, "to_timezone", |name, h| {
+        let date = date(name, param(name, h, 0)?)?;
+
+        let tz = match param(name, h, 1)? {
+            JsonValue::String(tz) => zone(name, tz)?,
+            tz => {
+                return Err(RenderError::new(format!(
+                    "The time zone of the `{}` helper needs to be a string, but it is {}.",
+                    name,
+                    type_name(tz)
+                )))
+            },
+        };
+
+        let converted = match tz {
+            Zone::Named(tz) => date.with_timezone(&tz).to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            Zone::Fixed(offset) => {
+                date.with_timezone(&offset).to_rfc3339_opts(SecondsFormat::AutoSi, true)
+            },
+        };
+
+        Ok(JsonValue::String(converted))
+    });
+
+    register_value_helper(handlebars, "timestamp", |name, h| {
+        Ok(JsonValue::from(date(name, param(name, h, 0)?)?.timestamp()))
+    });
+
+    handlebars.register_helper(
+        "time_ago",
+        Box::new(ClockHelper {
+            name: "time_ago",
+            now:  now.clone(),
+            f:    |name, h, now| {
+                Ok(JsonValue::String(time_ago(date(name, param(name, h, 0)?)?, now)))
+            },
+        }),
+    );
+
+    handlebars.register_helper(
+        "now",
+        Box::new(ClockHelper {
+            name: "now",
+            now,
+            f: |_, _, now| Ok(JsonValue::String(now.to_rfc3339_opts(SecondsFormat::AutoSi, true))),
+        }),
+    );
+}
+
+/// A helper which needs the current time.
+struct ClockHelper {
+    name: &'static str,
+    now:  Now,
+    f:    fn(&str, &Helper, DateTime<Utc>) -> Result<JsonValue, RenderError>,
+}
+
+impl HelperDef for ClockHelper {
+    #[inline]
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        (self.f)(self.name, h, (self.now)()).map(ScopedJson::Derived)
+    }
+}
+
+/// An IANA time zone or a fixed offset.
+enum Zone {
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+#[inline]
+fn zone(name: &str, tz: &str) -> Result<Zone, RenderError> {
+    if let Ok(tz) = Tz::from_str(tz) {
+        return Ok(Zone::Named(tz));
+    }
+
+    FixedOffset::from_str(tz).map(Zone::Fixed).map_err(|_| {
+        RenderError::new(format!("The `{}` helper does not know the time zone {:?}.", name, tz))
+    })
+}
+
+/// Parse an RFC 3339 or ISO 8601 string, or a Unix timestamp.
+fn date(name: &str, value: &JsonValue) -> Result<DateTime<FixedOffset>, RenderError> {
+    let utc = FixedOffset::east_opt(0).unwrap();
+
+    let date = match value {
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(secs) => DateTime::from_timestamp(secs, 0),
+            None => n.as_f64().and_then(|f| {
+                let secs = f.floor();
+
+                DateTime::from_timestamp(secs as i64, ((f - secs) * 1e9) as u32)
+            }),
+        }
+        .map(|date| date.with_timezone(&utc)),
+        JsonValue::String(s) => DateTime::parse_from_rfc3339(s)
+            .or_else(|_| DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f%z"))
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+                    .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f"))
+                    .or_else(|_| {
+                        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                            .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+                    })
+                    .ok()
+                    .map(|date| utc.from_utc_datetime(&date))
+            }),
+        _ => {
+            return Err(RenderError::new(format!(
+                "The `{}` helper needs a date string or a Unix timestamp, but got {}.",
+                name,
+                type_name(value)
+            )))
+        },
+    };
+
+    date.ok_or_else(|| {
+        RenderError::new(format!("The `{}` helper cannot parse the date {}.", name, value))
+    })
+}
+
+#[inline]
+fn format_date<Z: TimeZone>(
+    name: &str,
+    date: DateTime<Z>,
+    format: &str,
+) -> Result<String, RenderError>
+where
+    Z::Offset: std::fmt::Display, {
+    let items = StrftimeItems::new(format).collect::<Vec<Item>>();
+
+    let error =
+        || RenderError::new(format!("The `{}` helper cannot use the format {:?}.", name, format));
+
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(error());
+    }
+
+    let mut s = String::new();
+
+    write!(s, "{}", date.format_with_items(items.into_iter())).map_err(|_| error())?;
+
+    Ok(s)
+}
+
+/// Describe a date relative to `now`.
+fn time_ago(date: DateTime<FixedOffset>, now: DateTime<Utc>) -> String {
+    const UNITS: [(i64, &str); 6] = [
+        (365 * 24 * 60 * 60, "year"),
+        (30 * 24 * 60 * 60, "month"),
+        (7 * 24 * 60 * 60, "week"),
+        (24 * 60 * 60, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ];
+
+    let seconds = now.signed_duration_since(date).num_seconds();
+    let abs = seconds.unsigned_abs() as i64;
+
+    if abs < 45 {
+        return String::from("just now");
+    }
+
+    // `abs` is at least 45 seconds, so it is rounded to at least a minute
+    let (unit, unit_name) =
+        UNITS.iter().copied().find(|(unit, _)| abs >= *unit).unwrap_or(UNITS[UNITS.len() - 1]);
+
+    let count = (abs / unit).max(1);
+    let plural = if count == 1 { "" } else { "s" };
+
+    if seconds >= 0 {
+        format!("{} {}{} ago", count, unit_name, plural)
+    } else {
+        format!("in {} {}{}", count, unit_name, plural)
+    }
+}
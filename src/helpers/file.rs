@@ -0,0 +1,223 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs, io,
+    path::PathBuf,
+    sync::{Arc, PoisonError, RwLock},
+    time::SystemTime,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, JsonValue, Output, RenderContext,
+    RenderError, ScopedJson,
+};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+#[derive(Debug)]
+struct IncludedFile {
+    content:   Cow<'static, [u8]>,
+    /// The SHA-384 SRI hash, which is computed when the file is included.
+    integrity: String,
+    source:    Option<(PathBuf, Option<SystemTime>)>,
+}
+
+impl IncludedFile {
+    #[inline]
+    fn new(content: Cow<'static, [u8]>, source: Option<(PathBuf, Option<SystemTime>)>) -> Self {
+        IncludedFile {
+            integrity: integrity::<Sha384>("sha384", &content),
+            content,
+            source,
+        }
+    }
+}
+
+/// The files for the `sri` and `include_raw` helpers. Use the `handlebars_files_initialize!` macro in the fairing to include them, so that they are embedded in release builds and read again whenever they are modified in debug builds.
+#[derive(Debug, Default)]
+pub struct IncludedFiles {
+    files: RwLock<HashMap<&'static str, Arc<IncludedFile>>>,
+}
+
+impl IncludedFiles {
+    /// Create an instance of `IncludedFiles`.
+    #[inline]
+    pub fn new() -> IncludedFiles {
+        IncludedFiles::default()
+    }
+
+    /// Include the content of a file which is embedded in the executable binary file.
+    #[inline]
+    pub fn insert_bytes(&mut self, name: &'static str, content: &'static [u8]) {
+        self.files
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(name, Arc::new(IncludedFile::new(Cow::Borrowed(content), None)));
+    }
+
+    /// Include a file from a path and it can be reloaded automatically.
+    #[inline]
+    pub fn insert_file<P: Into<PathBuf>>(
+        &mut self,
+        name: &'static str,
+        file_path: P,
+    ) -> Result<(), io::Error> {
+        let file_path = file_path.into();
+
+        let mtime = file_path.metadata()?.modified().ok();
+
+        let content = fs::read(&file_path)?;
+
+        self.files.get_mut().unwrap_or_else(PoisonError::into_inner).insert(
+            name,
+            Arc::new(IncludedFile::new(Cow::Owned(content), Some((file_path, mtime)))),
+        );
+
+        Ok(())
+    }
+
+    /// Get the content of a file. A file from a path is read again if it has been modified.
+    #[inline]
+    pub fn get<S: AsRef<str>>(&self, name: S) -> Result<Option<Cow<'static, [u8]>>, io::Error> {
+        Ok(self.get_file(name.as_ref())?.map(|file| file.content.clone()))
+    }
+
+    fn get_file(&self, name: &str) -> Result<Option<Arc<IncludedFile>>, io::Error> {
+        let file = match self.files.read().unwrap_or_else(PoisonError::into_inner).get(name) {
+            Some(file) => file.clone(),
+            None => return Ok(None),
+        };
+
+        let (file_path, mtime) = match file.source.as_ref() {
+            Some(source) => source,
+            None => return Ok(Some(file)),
+        };
+
+        let new_mtime = file_path.metadata()?.modified().ok();
+
+        let reload = match (mtime, new_mtime) {
+            (Some(mtime), Some(new_mtime)) => new_mtime > *mtime,
+            _ => true,
+        };
+
+        if !reload {
+            return Ok(Some(file));
+        }
+
+        let content = fs::read(file_path)?;
+
+        let file =
+            Arc::new(IncludedFile::new(Cow::Owned(content), Some((file_path.clone(), new_mtime))));
+
+        let mut files = self.files.write().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some((name, _)) = files.remove_entry(name) {
+            files.insert(name, file.clone());
+        }
+
+        Ok(Some(file))
+    }
+
+    #[inline]
+    fn lookup(&self, helper_name: &str, h: &Helper) -> Result<Arc<IncludedFile>, RenderError> {
+        let name = h.param(0).and_then(|param| param.value().as_str()).ok_or_else(|| {
+            RenderError::new(format!(
+                "The `{}` helper needs the name of a file as its first parameter.",
+                helper_name
+            ))
+        })?;
+
+        self.get_file(name)
+            .map_err(|err| {
+                RenderError::new(format!("The file `{}` cannot be read: {}", name, err))
+            })?
+            .ok_or_else(|| RenderError::new(format!("The file `{}` is not included.", name)))
+    }
+}
+
+/// Compute an SRI hash, like `sha384-...`.
+#[inline]
+fn integrity<D: Digest>(algorithm: &str, content: &[u8]) -> String {
+    format!("{}-{}", algorithm, STANDARD.encode(D::digest(content)))
+}
+
+/// Register `{{sri "static/app.js"}}` and `{{include_raw "static/icons/logo.svg"}}` with included files. Use the `handlebars_files_initialize!` macro in the fairing instead of calling this function directly.
+///
+/// * `sri` returns the SRI hash of a file, which is SHA-384 by default. `algorithm="sha256"` and `algorithm="sha512"` are also supported. Inside attributes, the `=` padding of SHA-256 hashes is HTML-escaped by `{{...}}`, which browsers decode.
+/// * `include_raw` writes the content of a file as it is, without HTML-escaping, so it is meant for trusted files like SVG icons. The file needs to be UTF-8.
+///
+/// Files which are not included are errors.
+pub fn register_file_helpers(handlebars: &mut Handlebars, files: IncludedFiles) {
+    let files = Arc::new(files);
+
+    handlebars.register_helper(
+        "sri",
+        Box::new(SriHelper {
+            files: files.clone()
+        }),
+    );
+
+    handlebars.register_helper(
+        "include_raw",
+        Box::new(IncludeRawHelper {
+            files,
+        }),
+    );
+}
+
+struct SriHelper {
+    files: Arc<IncludedFiles>,
+}
+
+impl HelperDef for SriHelper {
+    #[inline]
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let file = self.files.lookup("sri", h)?;
+
+        let integrity = match h.hash_get("algorithm").and_then(|value| value.value().as_str()) {
+            None | Some("sha384") => file.integrity.clone(),
+            Some("sha256") => integrity::<Sha256>("sha256", &file.content),
+            Some("sha512") => integrity::<Sha512>("sha512", &file.content),
+            Some(algorithm) => {
+                return Err(RenderError::new(format!(
+                    "The `sri` helper does not support the algorithm {:?}.",
+                    algorithm
+                )))
+            },
+        };
+
+        Ok(ScopedJson::Derived(JsonValue::String(integrity)))
+    }
+}
+
+struct IncludeRawHelper {
+    files: Arc<IncludedFiles>,
+}
+
+impl HelperDef for IncludeRawHelper {
+    #[inline]
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let file = self.files.lookup("include_raw", h)?;
+
+        let content = std::str::from_utf8(&file.content).map_err(|_| {
+            RenderError::new("The `include_raw` helper can only include UTF-8 files.")
+        })?;
+
+        out.write(content)?;
+
+        Ok(())
+    }
+}
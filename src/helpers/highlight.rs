@@ -0,0 +1,123 @@
+use handlebars::{
+    html_escape, Context, Handlebars, Helper, HelperDef, HelperResult, JsonValue, Output,
+    RenderContext, RenderError, Renderable, StringOutput,
+};
+use once_cell::sync::Lazy;
+use syntect::{
+    highlighting::ThemeSet,
+    html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+use super::{dedent, hash_str, type_name};
+
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed {
+    prefix: "hl-"
+};
+
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+
+/// Highlight code as `<pre class="hl"><code class="language-...">...</code></pre>`. Unknown languages are not highlighted.
+pub(super) fn highlight(code: &str, lang: Option<&str>) -> Result<String, syntect::Error> {
+    let syntax = lang
+        .and_then(|lang| SYNTAX_SET.find_syntax_by_token(lang))
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+
+    let mut generator =
+        ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAX_SET, CLASS_STYLE);
+
+    for line in LinesWithEndings::from(code) {
+        generator.parse_html_for_line_which_includes_newline(line)?;
+    }
+
+    let mut html = String::from("<pre class=\"hl\"><code");
+
+    if let Some(lang) = lang {
+        html.push_str(" class=\"language-");
+        html.push_str(&html_escape(lang));
+        html.push('"');
+    }
+
+    html.push('>');
+    html.push_str(&generator.finalize());
+    html.push_str("</code></pre>");
+
+    Ok(html)
+}
+
+/// Generate the CSS of a theme bundled with `syntect` for the HTML of the `highlight` helper, such as `InspiredGitHub`, `Solarized (light)`, `Solarized (dark)`, `base16-ocean.dark` and `base16-ocean.light`. Return `None` if the theme does not exist. Only available with the `helper_highlight` feature.
+pub fn highlight_css(theme: &str) -> Option<String> {
+    let themes = ThemeSet::load_defaults();
+
+    themes
+        .themes
+        .get(theme)
+        .and_then(|theme| css_for_theme_with_class_style(theme, CLASS_STYLE).ok())
+}
+
+/// `{{#highlight lang="rust"}}...{{/highlight}}` and `{{highlight code lang="rust"}}`.
+///
+/// The code is highlighted when the template is rendered, as HTML whose classes are prefixed with `hl-`, like `<span class="hl-keyword hl-rust">`, so no JavaScript is needed. Use `highlight_css` to generate a stylesheet. The HTML is written as it is, and the code is escaped. In the block form, the block is rendered first, its common indentation and surrounding blank lines are removed, and `\{{` can be used to write `{{`.
+///
+/// Unknown languages are rendered as plain text. With the `helper_markdown` feature, the fenced code blocks of the `markdown` helper are highlighted as well.
+pub(super) fn register(handlebars: &mut Handlebars) {
+    handlebars.register_helper("highlight", Box::new(HighlightHelper));
+}
+
+struct HighlightHelper;
+
+impl HelperDef for HighlightHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let name = "highlight";
+
+        let lang = hash_str(name, h, "lang")?;
+
+        let mut code = match h.template() {
+            Some(template) => {
+                let mut buffer = StringOutput::new();
+
+                template.render(r, ctx, rc, &mut buffer)?;
+
+                let code = buffer.into_string().map_err(RenderError::from)?;
+
+                dedent(&code).trim_start_matches(['\n', '\r']).trim_end().to_string()
+            },
+            None => match h.param(0).map(|param| param.value()) {
+                Some(JsonValue::String(code)) => code.clone(),
+                Some(JsonValue::Null) => String::new(),
+                Some(value) => {
+                    return Err(RenderError::new(format!(
+                        "The `{}` helper needs a string, but got {}.",
+                        name,
+                        type_name(value)
+                    )))
+                },
+                None => {
+                    return Err(RenderError::new(format!(
+                        "The `{}` helper needs a parameter or a block.",
+                        name
+                    )))
+                },
+            },
+        };
+
+        if !code.is_empty() && !code.ends_with('\n') {
+            code.push('\n');
+        }
+
+        let html = highlight(&code, lang)
+            .map_err(|err| RenderError::new(format!("The code cannot be highlighted: {}", err)))?;
+
+        out.write(&html)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use handlebars::{Handlebars, JsonValue};
+
+use super::{param, register_value_helper};
+
+/// `{{json value}}` and `{{json_pretty value}}`.
+///
+/// Both serialize any value, including arrays and objects, to JSON, and escape `<`, `>`, `&`, U+2028 and U+2029 as `\u003c`, `\u003e`, `\u0026`, `\u2028` and `\u2029`. The result is still valid JSON and JavaScript, but it can never close a `<script>` element, start an HTML comment or end a JavaScript string literal.
+///
+/// Use `{{{json data}}}` inside `<script>`, where HTML entities are not decoded, and `{{json data}}` inside HTML attributes, where the quotes need to be HTML-escaped.
+pub(super) fn register(handlebars: &mut Handlebars) {
+    register_value_helper(handlebars, "json", |name, h| {
+        let json = param(name, h, 0)?.to_string();
+
+        Ok(JsonValue::String(escape(&json)))
+    });
+
+    register_value_helper(handlebars, "json_pretty", |name, h| {
+        // the alternate form of `Display` is the pretty-printed JSON
+        let json = format!("{:#}", param(name, h, 0)?);
+
+        Ok(JsonValue::String(escape(&json)))
+    });
+}
+
+/// Escape the characters which are unsafe in `<script>` elements.
+fn escape(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+
+    for c in json.chars() {
+        match c {
+            '<' => escaped.push_str("\\u003c"),
+            '>' => escaped.push_str("\\u003e"),
+            '&' => escaped.push_str("\\u0026"),
+            '\u{2028}' => escaped.push_str("\\u2028"),
+            '\u{2029}' => escaped.push_str("\\u2029"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
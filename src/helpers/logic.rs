@@ -0,0 +1,36 @@
+use handlebars::{Handlebars, JsonValue};
+
+use super::{is_truthy, param, params, register_value_helper};
+
+/// `{{and a b ...}}`, `{{or a b ...}}`, `{{not a}}`, `{{default value fallback}}` and `{{coalesce a b ...}}`.
+///
+/// `and`, `or` and `not` use the same truthiness as the built-in `if` helper, so `false`, `null`, `0`, `""`, `[]` and `{}` are falsy. `default` returns the fallback if the value is `null`, missing or an empty string. `coalesce` returns the first parameter which is not `null` or missing. `and`, `or` and `not` replace the built-in helpers of Handlebars with the same names, so the `helpers` feature does not enable them.
+pub(super) fn register(handlebars: &mut Handlebars) {
+    register_value_helper(handlebars, "and", |name, h| {
+        Ok(JsonValue::Bool(params(name, h)?.into_iter().all(is_truthy)))
+    });
+
+    register_value_helper(handlebars, "or", |name, h| {
+        Ok(JsonValue::Bool(params(name, h)?.into_iter().any(is_truthy)))
+    });
+
+    register_value_helper(handlebars, "not", |name, h| {
+        Ok(JsonValue::Bool(!is_truthy(param(name, h, 0)?)))
+    });
+
+    register_value_helper(handlebars, "default", |name, h| {
+        let value = param(name, h, 0)?;
+
+        let value = match value {
+            JsonValue::Null => param(name, h, 1)?,
+            JsonValue::String(s) if s.is_empty() => param(name, h, 1)?,
+            _ => value,
+        };
+
+        Ok(value.clone())
+    });
+
+    register_value_helper(handlebars, "coalesce", |name, h| {
+        Ok(params(name, h)?.into_iter().find(|v| !v.is_null()).cloned().unwrap_or(JsonValue::Null))
+    });
+}
@@ -0,0 +1,166 @@
+use ammonia::Builder;
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, JsonValue, Output, RenderContext,
+    RenderError, Renderable, StringOutput,
+};
+use pulldown_cmark::{html, Options, Parser};
+#[cfg(feature = "helper_highlight")]
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag, TagEnd};
+
+use super::{dedent, type_name};
+
+/// `{{markdown body}}` and `{{#markdown}}...{{/markdown}}`.
+///
+/// The Markdown is rendered as CommonMark and the HTML is sanitized with the default allow-list of `ammonia`, which removes scripts, styles, event handler attributes and unsafe URLs, and adds `rel="noopener noreferrer"` to links. The HTML is written as it is, so `{{markdown body}}` does not escape it again.
+///
+/// With the `helper_highlight` feature, fenced code blocks with a language, like ` ```rust `, are highlighted like the `highlight` helper does, and the `class` attributes of `pre`, `code` and `span` elements are kept.
+///
+/// `tables=true`, `footnotes=true`, `strikethrough=true` and `tasklists=true` enable the GitHub-flavored extensions. In the block form, the block is rendered first and its common indentation is removed, so that it does not become a code block.
+pub(super) fn register(handlebars: &mut Handlebars) {
+    #[allow(unused_mut)]
+    let mut sanitizer = Builder::default();
+
+    // the highlighted code blocks need their classes
+    #[cfg(feature = "helper_highlight")]
+    sanitizer
+        .add_tag_attributes("pre", ["class"])
+        .add_tag_attributes("code", ["class"])
+        .add_tag_attributes("span", ["class"]);
+
+    handlebars.register_helper(
+        "markdown",
+        Box::new(MarkdownHelper {
+            sanitizer,
+        }),
+    );
+}
+
+struct MarkdownHelper {
+    sanitizer: Builder<'static>,
+}
+
+impl HelperDef for MarkdownHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let name = "markdown";
+
+        let options = options(name, h)?;
+
+        let html = match h.template() {
+            Some(template) => {
+                let mut buffer = StringOutput::new();
+
+                template.render(r, ctx, rc, &mut buffer)?;
+
+                let markdown = buffer.into_string().map_err(RenderError::from)?;
+
+                render(&dedent(&markdown), options)
+            },
+            None => match h.param(0).map(|param| param.value()) {
+                Some(JsonValue::String(markdown)) => render(markdown, options),
+                Some(JsonValue::Null) => String::new(),
+                Some(value) => {
+                    return Err(RenderError::new(format!(
+                        "The `{}` helper needs a string, but got {}.",
+                        name,
+                        type_name(value)
+                    )))
+                },
+                None => {
+                    return Err(RenderError::new(format!(
+                        "The `{}` helper needs a parameter or a block.",
+                        name
+                    )))
+                },
+            },
+        };
+
+        out.write(&self.sanitizer.clean(&html).to_string())?;
+
+        Ok(())
+    }
+}
+
+#[inline]
+fn options(name: &str, h: &Helper) -> Result<Options, RenderError> {
+    let mut options = Options::empty();
+
+    for (key, option) in [
+        ("tables", Options::ENABLE_TABLES),
+        ("footnotes", Options::ENABLE_FOOTNOTES),
+        ("strikethrough", Options::ENABLE_STRIKETHROUGH),
+        ("tasklists", Options::ENABLE_TASKLISTS),
+    ] {
+        match h.hash_get(key).map(|value| value.value()) {
+            Some(JsonValue::Bool(true)) => options.insert(option),
+            Some(JsonValue::Bool(false)) | Some(JsonValue::Null) | None => (),
+            Some(value) => {
+                return Err(RenderError::new(format!(
+                    "The `{}` argument of the `{}` helper needs to be a boolean, but it is {}.",
+                    key,
+                    name,
+                    type_name(value)
+                )))
+            },
+        }
+    }
+
+    Ok(options)
+}
+
+#[inline]
+fn render(markdown: &str, options: Options) -> String {
+    let mut html = String::with_capacity(markdown.len() * 3 / 2);
+
+    #[cfg(not(feature = "helper_highlight"))]
+    html::push_html(&mut html, Parser::new_ext(markdown, options));
+
+    #[cfg(feature = "helper_highlight")]
+    html::push_html(&mut html, highlight_code_blocks(Parser::new_ext(markdown, options)));
+
+    html
+}
+
+/// Replace fenced code blocks with a language with highlighted HTML.
+#[cfg(feature = "helper_highlight")]
+fn highlight_code_blocks<'a, I: Iterator<Item = Event<'a>>>(
+    events: I,
+) -> impl Iterator<Item = Event<'a>> {
+    let mut code_block: Option<(CowStr<'a>, String)> = None;
+
+    events.filter_map(move |event| match (&mut code_block, event) {
+        (None, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))) => {
+            // the info string can be like `rust,ignore`
+            let lang = info.split([' ', ',']).next().unwrap_or("");
+
+            if lang.is_empty() {
+                Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))))
+            } else {
+                code_block = Some((CowStr::from(lang.to_string()), String::new()));
+
+                None
+            }
+        },
+        (Some((_, code)), Event::Text(text)) => {
+            code.push_str(&text);
+
+            None
+        },
+        (Some(_), Event::End(TagEnd::CodeBlock)) => {
+            let (lang, code) = code_block.take().unwrap();
+
+            let html = super::highlight::highlight(&code, Some(&lang)).unwrap_or_else(|_| {
+                format!("<pre><code>{}</code></pre>", handlebars::html_escape(&code))
+            });
+
+            Some(Event::Html(CowStr::from(html)))
+        },
+        (_, event) => Some(event),
+    })
+}
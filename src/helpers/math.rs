@@ -0,0 +1,326 @@
+use std::cmp::Ordering;
+
+use handlebars::{Handlebars, Helper, JsonValue, RenderError};
+
+use super::{hash_str, param, params, register_value_helper, type_name};
+
+/// The maximum number of decimal places.
+const MAX_DECIMALS: u64 = 20;
+
+/// `{{add a b ...}}`, `{{sub a b}}`, `{{mul a b ...}}`, `{{div a b}}`, `{{mod a b}}`, `{{round x decimals=2}}`, `{{min a b ...}}`, `{{max a b ...}}`, `{{format_number x decimals=2 sep="," point="."}}`, `{{percent x decimals=1}}` and `{{bytes x decimals=1 si=false}}`.
+///
+/// Integers stay integers as long as the result fits in `i64` and, for `div`, the division is exact. Otherwise the result is a float. `min` and `max` return the winning parameter as it is. Parameters which are not numbers, and `decimals` greater than 20, are errors.
+pub(super) fn register(handlebars: &mut Handlebars) {
+    register_value_helper(handlebars, "add", |name, h| {
+        fold(name, h, i64::checked_add, |a, b| a + b)
+    });
+
+    register_value_helper(handlebars, "sub", |name, h| {
+        binary(name, h, i64::checked_sub, |a, b| a - b)
+    });
+
+    register_value_helper(handlebars, "mul", |name, h| {
+        fold(name, h, i64::checked_mul, |a, b| a * b)
+    });
+
+    register_value_helper(handlebars, "div", |name, h| {
+        let divisor = number(name, param(name, h, 1)?)?;
+
+        if divisor.as_f64() == 0.0 {
+            return Err(RenderError::new(format!("The `{}` helper cannot divide by zero.", name)));
+        }
+
+        binary(
+            name,
+            h,
+            |a, b| if a.checked_rem(b) == Some(0) { a.checked_div(b) } else { None },
+            |a, b| a / b,
+        )
+    });
+
+    register_value_helper(handlebars, "mod", |name, h| {
+        let divisor = number(name, param(name, h, 1)?)?;
+
+        if divisor.as_f64() == 0.0 {
+            return Err(RenderError::new(format!("The `{}` helper cannot divide by zero.", name)));
+        }
+
+        binary(name, h, i64::checked_rem, |a, b| a % b)
+    });
+
+    register_value_helper(handlebars, "round", |name, h| {
+        let value = number(name, param(name, h, 0)?)?;
+        let decimals = hash_decimals(name, h)?.unwrap_or(0);
+
+        match value {
+            Num::Int(_) => value.into_json(name),
+            Num::Float(f) if decimals == 0 => {
+                let f = f.round();
+
+                if f.abs() < i64::MAX as f64 {
+                    Num::Int(f as i64).into_json(name)
+                } else {
+                    Num::Float(f).into_json(name)
+                }
+            },
+            Num::Float(f) => {
+                let scale = 10f64.powi(decimals as i32);
+                let scaled = f * scale;
+
+                // a float this large has no decimal places to round
+                if scaled.is_finite() {
+                    Num::Float(scaled.round() / scale).into_json(name)
+                } else {
+                    Num::Float(f).into_json(name)
+                }
+            },
+        }
+    });
+
+    register_value_helper(handlebars, "min", |name, h| extreme(name, h, Ordering::Less));
+
+    register_value_helper(handlebars, "max", |name, h| extreme(name, h, Ordering::Greater));
+
+    register_value_helper(handlebars, "format_number", |name, h| {
+        let value = number(name, param(name, h, 0)?)?;
+        let decimals = hash_decimals(name, h)?.map(|d| d as usize);
+        let sep = hash_str(name, h, "sep")?.unwrap_or(",");
+        let point = hash_str(name, h, "point")?.unwrap_or(".");
+
+        Ok(JsonValue::String(format_number(value, decimals, sep, point)))
+    });
+
+    register_value_helper(handlebars, "percent", |name, h| {
+        let value = number(name, param(name, h, 0)?)?.as_f64() * 100.0;
+        let decimals = hash_decimals(name, h)?.unwrap_or(0) as usize;
+
+        Ok(JsonValue::String(format!("{:.*}%", decimals, value)))
+    });
+
+    register_value_helper(handlebars, "bytes", |name, h| {
+        let value = number(name, param(name, h, 0)?)?;
+        let decimals = hash_decimals(name, h)?.unwrap_or(1) as usize;
+
+        let si = match h.hash_get("si").map(|si| si.value()) {
+            Some(JsonValue::Bool(si)) => *si,
+            Some(JsonValue::Null) | None => false,
+            Some(si) => {
+                return Err(RenderError::new(format!(
+                    "The `si` argument of the `{}` helper needs to be a boolean, but it is {}.",
+                    name,
+                    type_name(si)
+                )))
+            },
+        };
+
+        Ok(JsonValue::String(format_bytes(value, decimals, si)))
+    });
+}
+
+/// An integer or a float.
+#[derive(Debug, Clone, Copy)]
+enum Num {
+    Int(i64),
+    Float(f64),
+}
+
+impl Num {
+    #[inline]
+    fn as_f64(self) -> f64 {
+        match self {
+            Num::Int(i) => i as f64,
+            Num::Float(f) => f,
+        }
+    }
+
+    #[inline]
+    fn compare(self, other: Num) -> Ordering {
+        match (self, other) {
+            (Num::Int(a), Num::Int(b)) => a.cmp(&b),
+            // numbers from JSON are never NaN
+            _ => self.as_f64().partial_cmp(&other.as_f64()).unwrap_or(Ordering::Equal),
+        }
+    }
+
+    #[inline]
+    fn into_json(self, name: &str) -> Result<JsonValue, RenderError> {
+        match self {
+            Num::Int(i) => Ok(JsonValue::from(i)),
+            Num::Float(f) if f.is_finite() => Ok(JsonValue::from(f)),
+            Num::Float(_) => Err(RenderError::new(format!(
+                "The result of the `{}` helper is not a finite number.",
+                name
+            ))),
+        }
+    }
+}
+
+#[inline]
+fn number(name: &str, value: &JsonValue) -> Result<Num, RenderError> {
+    match value {
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) => Ok(Num::Int(i)),
+            // `as_f64` always works on a number
+            None => Ok(Num::Float(n.as_f64().unwrap_or(f64::NAN))),
+        },
+        _ => Err(RenderError::new(format!(
+            "The `{}` helper needs numbers, but got {}.",
+            name,
+            type_name(value)
+        ))),
+    }
+}
+
+#[inline]
+fn apply(
+    a: Num,
+    b: Num,
+    int_op: impl Fn(i64, i64) -> Option<i64>,
+    float_op: impl Fn(f64, f64) -> f64,
+) -> Num {
+    match (a, b) {
+        (Num::Int(a), Num::Int(b)) => match int_op(a, b) {
+            Some(i) => Num::Int(i),
+            None => Num::Float(float_op(a as f64, b as f64)),
+        },
+        _ => Num::Float(float_op(a.as_f64(), b.as_f64())),
+    }
+}
+
+#[inline]
+fn binary(
+    name: &str,
+    h: &Helper,
+    int_op: impl Fn(i64, i64) -> Option<i64>,
+    float_op: impl Fn(f64, f64) -> f64,
+) -> Result<JsonValue, RenderError> {
+    let a = number(name, param(name, h, 0)?)?;
+    let b = number(name, param(name, h, 1)?)?;
+
+    apply(a, b, int_op, float_op).into_json(name)
+}
+
+#[inline]
+fn fold(
+    name: &str,
+    h: &Helper,
+    int_op: impl Fn(i64, i64) -> Option<i64>,
+    float_op: impl Fn(f64, f64) -> f64,
+) -> Result<JsonValue, RenderError> {
+    let mut values = params(name, h)?.into_iter();
+
+    // `params` makes sure that there is at least one parameter
+    let mut result = number(name, values.next().unwrap_or(&JsonValue::Null))?;
+
+    for value in values {
+        result = apply(result, number(name, value)?, &int_op, &float_op);
+    }
+
+    result.into_json(name)
+}
+
+/// Find the smallest (`Ordering::Less`) or the largest (`Ordering::Greater`) parameter. Of equal parameters, the first one wins.
+#[inline]
+fn extreme(name: &str, h: &Helper, ordering: Ordering) -> Result<JsonValue, RenderError> {
+    let mut values = params(name, h)?.into_iter();
+
+    // `params` makes sure that there is at least one parameter
+    let mut result = number(name, values.next().unwrap_or(&JsonValue::Null))?;
+
+    for value in values {
+        let value = number(name, value)?;
+
+        if value.compare(result) == ordering {
+            result = value;
+        }
+    }
+
+    result.into_json(name)
+}
+
+#[inline]
+fn hash_u64(name: &str, h: &Helper, key: &str) -> Result<Option<u64>, RenderError> {
+    match h.hash_get(key).map(|v| v.value()) {
+        Some(JsonValue::Null) | None => Ok(None),
+        Some(value) => value.as_u64().map(Some).ok_or_else(|| {
+            RenderError::new(format!(
+                "The `{}` argument of the `{}` helper needs to be a non-negative integer.",
+                key, name
+            ))
+        }),
+    }
+}
+
+#[inline]
+fn hash_decimals(name: &str, h: &Helper) -> Result<Option<u64>, RenderError> {
+    match hash_u64(name, h, "decimals")? {
+        Some(decimals) if decimals > MAX_DECIMALS => Err(RenderError::new(format!(
+            "The `decimals` argument of the `{}` helper cannot be greater than {}.",
+            name, MAX_DECIMALS
+        ))),
+        decimals => Ok(decimals),
+    }
+}
+
+/// Format a number with a thousands separator. Without `decimals`, integers have no decimal places and floats have as many as they need.
+fn format_number(value: Num, decimals: Option<usize>, sep: &str, point: &str) -> String {
+    let s = match (value, decimals) {
+        (Num::Int(i), None) | (Num::Int(i), Some(0)) => i.to_string(),
+        (Num::Int(i), Some(decimals)) => format!("{}.{}", i, "0".repeat(decimals)),
+        (Num::Float(f), None) => f.to_string(),
+        (Num::Float(f), Some(decimals)) => format!("{:.*}", decimals, f),
+    };
+
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(s) => ("-", s),
+        None => ("", s.as_str()),
+    };
+
+    let (integer, fraction) = match s.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (s, None),
+    };
+
+    let mut result = String::from(sign);
+
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            result.push_str(sep);
+        }
+
+        result.push(c);
+    }
+
+    if let Some(fraction) = fraction {
+        result.push_str(point);
+        result.push_str(fraction);
+    }
+
+    result
+}
+
+/// Format a size in bytes with binary units (KiB, MiB, ...) or SI units (kB, MB, ...).
+fn format_bytes(value: Num, decimals: usize, si: bool) -> String {
+    const BINARY_UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    const SI_UNITS: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+
+    let (base, units) = if si { (1000.0, SI_UNITS) } else { (1024.0, BINARY_UNITS) };
+
+    let mut size = value.as_f64();
+
+    if size.abs() < base {
+        return match value {
+            Num::Int(i) => format!("{} B", i),
+            Num::Float(f) => format!("{} B", f),
+        };
+    }
+
+    let mut unit = 0;
+
+    while size.abs() >= base && unit < units.len() - 1 {
+        size /= base;
+        unit += 1;
+    }
+
+    format!("{:.*} {}", decimals, size, units[unit])
+}
@@ -0,0 +1,422 @@
+mod asset;
+#[cfg(feature = "helper_collection")]
+mod collection;
+mod compare;
+#[cfg(feature = "helper_date")]
+mod date;
+#[cfg(feature = "helper_file")]
+mod file;
+#[cfg(feature = "helper_highlight")]
+mod highlight;
+mod json;
+mod logic;
+#[cfg(feature = "helper_markdown")]
+mod markdown;
+mod math;
+mod pagination;
+#[cfg(feature = "helper_sanitize")]
+mod sanitize;
+#[cfg(feature = "helper_string")]
+mod string;
+mod url;
+
+use std::ops::{BitOr, BitOrAssign};
+
+pub use asset::{register_asset_helpers, Asset, AssetManifest};
+#[cfg(feature = "helper_date")]
+pub use date::register_date_helpers;
+#[cfg(feature = "helper_file")]
+pub use file::{register_file_helpers, IncludedFiles};
+use handlebars::{
+    handlebars_helper, Context, Handlebars, Helper, HelperDef, JsonValue, RenderContext,
+    RenderError, ScopedJson,
+};
+#[cfg(feature = "helper_highlight")]
+pub use highlight::highlight_css;
+pub use pagination::Pagination;
+#[cfg(feature = "helper_sanitize")]
+pub use sanitize::register_sanitize_helper;
+pub(crate) use url::UrlHelper;
+
+/// A set of the helpers provided by this crate.
+///
+/// ```rust,ignore
+/// HandlebarsResponse::fairing(|handlebars| {
+///     register_helpers(handlebars, HelperSet::COMPARE | HelperSet::LOGIC);
+///
+///     handlebars_resources_initialize!(handlebars, "index" => "views/index.hbs");
+/// })
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HelperSet {
+    bits: u32,
+}
+
+impl HelperSet {
+    /// All helpers.
+    pub const ALL: HelperSet = HelperSet {
+        bits: (1 << 15) - 1
+    };
+    /// `len`, `first`, `last`, `slice`, `sort_by`, `group_by`, `range`, `contains`, `keys` and `values`. Only available with the `helper_collection` feature.
+    #[cfg(feature = "helper_collection")]
+    pub const COLLECTION: HelperSet = HelperSet {
+        bits: 1 << 8
+    };
+    /// `eq`, `ne`, `gt`, `lt`, `gte`, `lte` and `in`.
+    pub const COMPARE: HelperSet = HelperSet {
+        bits: 1 << 4
+    };
+    /// `format_date`, `time_ago`, `to_timezone`, `timestamp` and `now`. Only available with the `helper_date` feature.
+    #[cfg(feature = "helper_date")]
+    pub const DATE: HelperSet = HelperSet {
+        bits: 1 << 9
+    };
+    /// `dec`.
+    pub const DEC: HelperSet = HelperSet {
+        bits: 1 << 1
+    };
+    /// `eq_str`.
+    pub const EQ_STR: HelperSet = HelperSet {
+        bits: 1 << 2
+    };
+    /// `highlight`. Only available with the `helper_highlight` feature.
+    #[cfg(feature = "helper_highlight")]
+    pub const HIGHLIGHT: HelperSet = HelperSet {
+        bits: 1 << 14
+    };
+    /// `inc`.
+    pub const INC: HelperSet = HelperSet {
+        bits: 1
+    };
+    /// `json` and `json_pretty`.
+    pub const JSON: HelperSet = HelperSet {
+        bits: 1 << 10
+    };
+    /// `and`, `or`, `not`, `default` and `coalesce`.
+    pub const LOGIC: HelperSet = HelperSet {
+        bits: 1 << 5
+    };
+    /// `markdown`. Only available with the `helper_markdown` feature.
+    #[cfg(feature = "helper_markdown")]
+    pub const MARKDOWN: HelperSet = HelperSet {
+        bits: 1 << 11
+    };
+    /// `add`, `sub`, `mul`, `div`, `mod`, `round`, `min`, `max`, `format_number`, `percent` and `bytes`.
+    pub const MATH: HelperSet = HelperSet {
+        bits: 1 << 6
+    };
+    /// `ne_str`.
+    pub const NE_STR: HelperSet = HelperSet {
+        bits: 1 << 3
+    };
+    /// No helpers.
+    pub const NONE: HelperSet = HelperSet {
+        bits: 0
+    };
+    /// `paginate`.
+    pub const PAGINATION: HelperSet = HelperSet {
+        bits: 1 << 13
+    };
+    /// `sanitize`. Only available with the `helper_sanitize` feature.
+    #[cfg(feature = "helper_sanitize")]
+    pub const SANITIZE: HelperSet = HelperSet {
+        bits: 1 << 12
+    };
+    /// `upper`, `lower`, `capitalize`, `truncate`, `slugify`, `replace`, `trim`, `pad`, `split`, `join` and `concat`. Only available with the `helper_string` feature.
+    #[cfg(feature = "helper_string")]
+    pub const STRING: HelperSet = HelperSet {
+        bits: 1 << 7
+    };
+
+    /// The helpers enabled by cargo features. The fairing of `HandlebarsResponse` registers them.
+    #[inline]
+    pub fn enabled() -> HelperSet {
+        let mut set = HelperSet::NONE;
+
+        if cfg!(feature = "helper_inc") {
+            set |= HelperSet::INC;
+        }
+
+        if cfg!(feature = "helper_dec") {
+            set |= HelperSet::DEC;
+        }
+
+        if cfg!(feature = "helper_eq_str") {
+            set |= HelperSet::EQ_STR;
+        }
+
+        if cfg!(feature = "helper_ne_str") {
+            set |= HelperSet::NE_STR;
+        }
+
+        if cfg!(feature = "helper_compare") {
+            set |= HelperSet::COMPARE;
+        }
+
+        if cfg!(feature = "helper_logic") {
+            set |= HelperSet::LOGIC;
+        }
+
+        if cfg!(feature = "helper_math") {
+            set |= HelperSet::MATH;
+        }
+
+        #[cfg(feature = "helper_string")]
+        {
+            set |= HelperSet::STRING;
+        }
+
+        #[cfg(feature = "helper_collection")]
+        {
+            set |= HelperSet::COLLECTION;
+        }
+
+        #[cfg(feature = "helper_date")]
+        {
+            set |= HelperSet::DATE;
+        }
+
+        if cfg!(feature = "helper_json") {
+            set |= HelperSet::JSON;
+        }
+
+        #[cfg(feature = "helper_markdown")]
+        {
+            set |= HelperSet::MARKDOWN;
+        }
+
+        if cfg!(feature = "helper_pagination") {
+            set |= HelperSet::PAGINATION;
+        }
+
+        #[cfg(feature = "helper_highlight")]
+        {
+            set |= HelperSet::HIGHLIGHT;
+        }
+
+        #[cfg(feature = "helper_sanitize")]
+        {
+            set |= HelperSet::SANITIZE;
+        }
+
+        set
+    }
+
+    /// Combine two sets.
+    #[inline]
+    pub const fn union(self, other: HelperSet) -> HelperSet {
+        HelperSet {
+            bits: self.bits | other.bits
+        }
+    }
+
+    /// Check if all helpers in `other` are in this set.
+    #[inline]
+    pub const fn contains(self, other: HelperSet) -> bool {
+        self.bits & other.bits == other.bits
+    }
+}
+
+impl BitOr for HelperSet {
+    type Output = HelperSet;
+
+    #[inline]
+    fn bitor(self, other: HelperSet) -> HelperSet {
+        self.union(other)
+    }
+}
+
+impl BitOrAssign for HelperSet {
+    #[inline]
+    fn bitor_assign(&mut self, other: HelperSet) {
+        *self = self.union(other);
+    }
+}
+
+/// Register the helpers in `set`, regardless of the `helper_*` features. The string, collection, date, Markdown, sanitization and highlighting helpers need their dependencies, so `HelperSet::STRING`, `HelperSet::COLLECTION`, `HelperSet::DATE`, `HelperSet::MARKDOWN`, `HelperSet::SANITIZE` and `HelperSet::HIGHLIGHT` only exist with the `helper_string`, `helper_collection`, `helper_date`, `helper_markdown`, `helper_sanitize` and `helper_highlight` features.
+pub fn register_helpers(handlebars: &mut Handlebars, set: HelperSet) {
+    if set.contains(HelperSet::INC) {
+        handlebars_helper!(inc: |x: i64| x + 1);
+
+        handlebars.register_helper("inc", Box::new(inc));
+    }
+
+    if set.contains(HelperSet::DEC) {
+        handlebars_helper!(dec: |x: i64| x - 1);
+
+        handlebars.register_helper("dec", Box::new(dec));
+    }
+
+    if set.contains(HelperSet::EQ_STR) {
+        handlebars_helper!(eq_str: |x: str, y: str| x == y);
+
+        handlebars.register_helper("eq_str", Box::new(eq_str));
+    }
+
+    if set.contains(HelperSet::NE_STR) {
+        handlebars_helper!(ne_str: |x: str, y: str| x != y);
+
+        handlebars.register_helper("ne_str", Box::new(ne_str));
+    }
+
+    if set.contains(HelperSet::COMPARE) {
+        compare::register(handlebars);
+    }
+
+    if set.contains(HelperSet::LOGIC) {
+        logic::register(handlebars);
+    }
+
+    if set.contains(HelperSet::MATH) {
+        math::register(handlebars);
+    }
+
+    #[cfg(feature = "helper_string")]
+    if set.contains(HelperSet::STRING) {
+        string::register(handlebars);
+    }
+
+    #[cfg(feature = "helper_collection")]
+    if set.contains(HelperSet::COLLECTION) {
+        collection::register(handlebars);
+    }
+
+    #[cfg(feature = "helper_date")]
+    if set.contains(HelperSet::DATE) {
+        register_date_helpers(handlebars, chrono::Utc::now);
+    }
+
+    if set.contains(HelperSet::JSON) {
+        json::register(handlebars);
+    }
+
+    #[cfg(feature = "helper_markdown")]
+    if set.contains(HelperSet::MARKDOWN) {
+        markdown::register(handlebars);
+    }
+
+    if set.contains(HelperSet::PAGINATION) {
+        pagination::register(handlebars);
+    }
+
+    #[cfg(feature = "helper_sanitize")]
+    if set.contains(HelperSet::SANITIZE) {
+        sanitize::register(handlebars);
+    }
+
+    #[cfg(feature = "helper_highlight")]
+    if set.contains(HelperSet::HIGHLIGHT) {
+        highlight::register(handlebars);
+    }
+}
+
+type HelperFn = fn(&str, &Helper) -> Result<JsonValue, RenderError>;
+
+/// A helper which computes a JSON value from its parameters, so that it can also be used in subexpressions.
+struct ValueHelper {
+    name: &'static str,
+    f:    HelperFn,
+}
+
+impl HelperDef for ValueHelper {
+    #[inline]
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        (self.f)(self.name, h).map(ScopedJson::Derived)
+    }
+}
+
+#[inline]
+fn register_value_helper(handlebars: &mut Handlebars, name: &'static str, f: HelperFn) {
+    handlebars.register_helper(
+        name,
+        Box::new(ValueHelper {
+            name,
+            f,
+        }),
+    );
+}
+
+/// Get a parameter, or return an error if it is not given.
+#[inline]
+fn param<'a>(name: &str, h: &'a Helper, index: usize) -> Result<&'a JsonValue, RenderError> {
+    h.param(index).map(|param| param.value()).ok_or_else(|| {
+        RenderError::new(format!(
+            "The `{}` helper needs at least {} parameter(s).",
+            name,
+            index + 1
+        ))
+    })
+}
+
+/// Get all parameters, or return an error if there is none.
+#[inline]
+fn params<'a>(name: &str, h: &'a Helper) -> Result<Vec<&'a JsonValue>, RenderError> {
+    param(name, h, 0)?;
+
+    Ok(h.params().iter().map(|param| param.value()).collect())
+}
+
+/// Get a string argument, or `None` if it is not given or `null`.
+#[inline]
+fn hash_str<'a>(name: &str, h: &'a Helper, key: &str) -> Result<Option<&'a str>, RenderError> {
+    match h.hash_get(key).map(|v| v.value()) {
+        Some(JsonValue::Null) | None => Ok(None),
+        Some(value) => value.as_str().map(Some).ok_or_else(|| {
+            RenderError::new(format!(
+                "The `{}` argument of the `{}` helper needs to be a string.",
+                key, name
+            ))
+        }),
+    }
+}
+
+/// The truthiness used by the built-in `if` helper.
+#[inline]
+fn is_truthy(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Bool(b) => *b,
+        JsonValue::Number(n) => n.as_f64().map(|f| f.is_normal()).unwrap_or(false),
+        JsonValue::Null => false,
+        JsonValue::String(s) => !s.is_empty(),
+        JsonValue::Array(a) => !a.is_empty(),
+        JsonValue::Object(o) => !o.is_empty(),
+    }
+}
+
+#[inline]
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Bool(_) => "a boolean",
+        JsonValue::Number(_) => "a number",
+        JsonValue::Null => "null",
+        JsonValue::String(_) => "a string",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+    }
+}
+
+/// Remove the indentation which all non-blank lines have in common.
+#[cfg(any(feature = "helper_markdown", feature = "helper_highlight"))]
+fn dedent(s: &str) -> String {
+    let indent = s
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start_matches([' ', '\t']).len())
+        .min()
+        .unwrap_or(0);
+
+    let mut result = String::with_capacity(s.len());
+
+    for line in s.lines() {
+        result.push_str(line.get(indent..).unwrap_or_else(|| line.trim_start()));
+        result.push('\n');
+    }
+
+    result
+}
@@ -0,0 +1,315 @@
+use handlebars::{
+    BlockContext, BlockParams, Context, Handlebars, Helper, HelperDef, HelperResult, JsonValue,
+    Output, RenderContext, RenderError, Renderable,
+};
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use super::type_name;
+
+/// The state of a paginated list, which can be serialized into the context of a template and rendered by the `paginate` block helper.
+///
+/// ```rust,ignore
+/// let pagination = Pagination::new(page, 20, total).with_url("/posts?page={page}");
+///
+/// handlebars_response!(cm, "posts", json!({ "posts": posts, "pagination": pagination }))
+/// ```
+///
+/// Pages start at 1. Besides `page`, `per_page`, `total` and `url`, the serialized object has `total_pages`, `offset`, `prev`, `next`, `prev_url` and `next_url`, where the page numbers and URLs which do not exist are `null`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pagination {
+    page:     u64,
+    per_page: u64,
+    total:    u64,
+    url:      Option<String>,
+}
+
+impl Pagination {
+    /// Create a `Pagination` instance. `page` and `per_page` are at least 1.
+    #[inline]
+    pub fn new(page: u64, per_page: u64, total: u64) -> Pagination {
+        Pagination {
+            page: page.max(1),
+            per_page: per_page.max(1),
+            total,
+            url: None,
+        }
+    }
+
+    /// Set the URL pattern of pages, in which `{page}` is replaced by the page number, like `/posts?page={page}`. Without a pattern, the URLs are like `?page=2`.
+    #[inline]
+    pub fn with_url<S: Into<String>>(mut self, url: S) -> Pagination {
+        self.url = Some(url.into());
+
+        self
+    }
+
+    /// The current page.
+    #[inline]
+    pub fn page(&self) -> u64 {
+        self.page
+    }
+
+    /// The number of items per page.
+    #[inline]
+    pub fn per_page(&self) -> u64 {
+        self.per_page
+    }
+
+    /// The number of all items.
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// The number of pages.
+    #[inline]
+    pub fn total_pages(&self) -> u64 {
+        self.total / self.per_page + u64::from(self.total % self.per_page != 0)
+    }
+
+    /// The number of items before the current page, which can be used as the offset of a query.
+    #[inline]
+    pub fn offset(&self) -> u64 {
+        (self.page - 1).saturating_mul(self.per_page)
+    }
+
+    /// The previous page, if any.
+    #[inline]
+    pub fn prev(&self) -> Option<u64> {
+        if self.page > 1 {
+            Some((self.page - 1).min(self.total_pages().max(1)))
+        } else {
+            None
+        }
+    }
+
+    /// The next page, if any.
+    #[inline]
+    pub fn next(&self) -> Option<u64> {
+        if self.page < self.total_pages() {
+            Some(self.page + 1)
+        } else {
+            None
+        }
+    }
+
+    /// Get the URL of a page.
+    #[inline]
+    pub fn url(&self, page: u64) -> String {
+        page_url(self.url.as_deref(), page)
+    }
+
+    /// The pages around the current page, where `None` is a gap. The first page, the last page and `window` pages on each side of the current page are always included, and a gap is never in place of only one page.
+    pub fn pages(&self, window: u64) -> Vec<Option<u64>> {
+        let total_pages = self.total_pages();
+
+        if total_pages == 0 {
+            return Vec::new();
+        }
+
+        let current = self.page.min(total_pages);
+
+        let start = current.saturating_sub(window).max(1);
+        let end = current.saturating_add(window).min(total_pages);
+
+        let mut pages = Vec::new();
+
+        let push = |pages: &mut Vec<Option<u64>>, page: u64| {
+            match pages.last() {
+                Some(Some(last)) if page - last == 2 => pages.push(Some(last + 1)),
+                Some(Some(last)) if page - last > 2 => pages.push(None),
+                _ => (),
+            }
+
+            pages.push(Some(page));
+        };
+
+        if start > 1 {
+            push(&mut pages, 1);
+        }
+
+        for page in start..=end {
+            push(&mut pages, page);
+        }
+
+        if end < total_pages {
+            push(&mut pages, total_pages);
+        }
+
+        pages
+    }
+}
+
+impl Serialize for Pagination {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let prev = self.prev();
+        let next = self.next();
+
+        let mut s = serializer.serialize_struct("Pagination", 10)?;
+
+        s.serialize_field("page", &self.page)?;
+        s.serialize_field("per_page", &self.per_page)?;
+        s.serialize_field("total", &self.total)?;
+        s.serialize_field("url", &self.url)?;
+        s.serialize_field("total_pages", &self.total_pages())?;
+        s.serialize_field("offset", &self.offset())?;
+        s.serialize_field("prev", &prev)?;
+        s.serialize_field("next", &next)?;
+        s.serialize_field("prev_url", &prev.map(|page| self.url(page)))?;
+        s.serialize_field("next_url", &next.map(|page| self.url(page)))?;
+
+        s.end()
+    }
+}
+
+#[inline]
+fn page_url(pattern: Option<&str>, page: u64) -> String {
+    match pattern {
+        Some(pattern) => pattern.replace("{page}", &page.to_string()),
+        None => format!("?page={}", page),
+    }
+}
+
+/// `{{#paginate pagination window=2}}...{{/paginate}}`.
+///
+/// The block is rendered for each item of `Pagination::pages`, with `number`, `url`, `is_current` and `is_gap` as its context (or its block parameter, like `as |item|`), and `@index`, `@first` and `@last`. The `number` and `url` of a gap are `null`. `window` is 2 by default, and `url="/posts/{page}"` overrides the URL pattern of the pagination. If there is only one page, the `{{else}}` block is rendered instead.
+pub(super) fn register(handlebars: &mut Handlebars) {
+    handlebars.register_helper("paginate", Box::new(PaginateHelper));
+}
+
+struct PaginateHelper;
+
+impl HelperDef for PaginateHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let name = "paginate";
+
+        let pagination = match h.param(0).map(|param| param.value()) {
+            Some(value) => from_json(name, value)?,
+            None => {
+                return Err(RenderError::new(format!(
+                    "The `{}` helper needs a pagination as its first parameter.",
+                    name
+                )))
+            },
+        };
+
+        let window = match h.hash_get("window").map(|value| value.value()) {
+            Some(value) => value.as_u64().ok_or_else(|| {
+                RenderError::new(format!(
+                    "The `window` argument of the `{}` helper needs to be a non-negative integer.",
+                    name
+                ))
+            })?,
+            None => 2,
+        };
+
+        let pattern = match h.hash_get("url").map(|value| value.value()) {
+            Some(JsonValue::String(pattern)) => Some(pattern.as_str()),
+            Some(JsonValue::Null) | None => pagination.url.as_deref(),
+            Some(value) => {
+                return Err(RenderError::new(format!(
+                    "The `url` argument of the `{}` helper needs to be a string, but it is {}.",
+                    name,
+                    type_name(value)
+                )))
+            },
+        };
+
+        if pagination.total_pages() <= 1 {
+            if let Some(template) = h.inverse() {
+                template.render(r, ctx, rc, out)?;
+            }
+
+            return Ok(());
+        }
+
+        let template = match h.template() {
+            Some(template) => template,
+            None => return Ok(()),
+        };
+
+        let pages = pagination.pages(window);
+        let last_index = pages.len() - 1;
+
+        for (index, page) in pages.into_iter().enumerate() {
+            let item = match page {
+                Some(number) => JsonValue::from_iter([
+                    ("number", JsonValue::from(number)),
+                    ("url", JsonValue::from(page_url(pattern, number))),
+                    ("is_current", JsonValue::Bool(number == pagination.page)),
+                    ("is_gap", JsonValue::Bool(false)),
+                ]),
+                None => JsonValue::from_iter([
+                    ("number", JsonValue::Null),
+                    ("url", JsonValue::Null),
+                    ("is_current", JsonValue::Bool(false)),
+                    ("is_gap", JsonValue::Bool(true)),
+                ]),
+            };
+
+            let mut block = BlockContext::new();
+
+            block.set_local_var("index", JsonValue::from(index));
+            block.set_local_var("first", JsonValue::Bool(index == 0));
+            block.set_local_var("last", JsonValue::Bool(index == last_index));
+
+            if let Some(block_param) = h.block_param() {
+                let mut params = BlockParams::new();
+
+                params.add_value(block_param, item.clone())?;
+
+                block.set_block_params(params);
+            }
+
+            block.set_base_value(item);
+
+            rc.push_block(block);
+
+            let result = template.render(r, ctx, rc, out);
+
+            rc.pop_block();
+
+            result?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Read a serialized `Pagination`. Only `page`, `per_page`, `total` and `url` are needed.
+fn from_json(helper_name: &str, value: &JsonValue) -> Result<Pagination, RenderError> {
+    let object = match value {
+        JsonValue::Object(object) => object,
+        _ => {
+            return Err(RenderError::new(format!(
+                "The `{}` helper needs a pagination, but got {}.",
+                helper_name,
+                type_name(value)
+            )))
+        },
+    };
+
+    let field = |key: &str| {
+        object.get(key).and_then(JsonValue::as_u64).ok_or_else(|| {
+            RenderError::new(format!(
+                "The pagination of the `{}` helper needs `{}` to be a non-negative integer.",
+                helper_name, key
+            ))
+        })
+    };
+
+    let mut pagination = Pagination::new(field("page")?, field("per_page")?, field("total")?);
+
+    if let Some(JsonValue::String(url)) = object.get("url") {
+        pagination.url = Some(url.clone());
+    }
+
+    Ok(pagination)
+}
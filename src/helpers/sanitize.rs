@@ -0,0 +1,70 @@
+use ammonia::Builder;
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, JsonRender, JsonValue, RenderContext, RenderError,
+    ScopedJson,
+};
+
+use super::{param, type_name};
+
+/// Register the `sanitize` helper with a custom allow-list. Call it in the callback of the fairing to replace the default one.
+///
+/// `{{{sanitize user_bio}}}` removes the tags, attributes and URL schemes which are not allowed by `builder` from the HTML. The result is safe to be output with triple-stash. With double-stash, it is HTML-escaped like any other value.
+///
+/// ```rust,ignore
+/// HandlebarsResponse::fairing(|handlebars| {
+///     let mut builder = ammonia::Builder::empty();
+///
+///     builder.add_tags(["b", "i", "a"]).add_tag_attributes("a", ["href"]);
+///
+///     register_sanitize_helper(handlebars, builder);
+///
+///     handlebars_resources_initialize!(handlebars, "index" => "views/index.hbs");
+/// })
+/// ```
+#[inline]
+pub fn register_sanitize_helper(handlebars: &mut Handlebars, builder: Builder<'static>) {
+    handlebars.register_helper(
+        "sanitize",
+        Box::new(SanitizeHelper {
+            builder,
+        }),
+    );
+}
+
+/// Register the `sanitize` helper with the default allow-list of `ammonia`.
+#[inline]
+pub(super) fn register(handlebars: &mut Handlebars) {
+    register_sanitize_helper(handlebars, Builder::default());
+}
+
+struct SanitizeHelper {
+    builder: Builder<'static>,
+}
+
+impl HelperDef for SanitizeHelper {
+    #[inline]
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let name = "sanitize";
+
+        let html = match param(name, h, 0)? {
+            JsonValue::String(html) => self.builder.clean(html).to_string(),
+            JsonValue::Null => String::new(),
+            value @ (JsonValue::Number(_) | JsonValue::Bool(_)) => value.render(),
+            value => {
+                return Err(RenderError::new(format!(
+                    "The `{}` helper needs a string, but got {}.",
+                    name,
+                    type_name(value)
+                )))
+            },
+        };
+
+        Ok(ScopedJson::Derived(JsonValue::String(html)))
+    }
+}
@@ -0,0 +1,196 @@
+use handlebars::{Handlebars, Helper, JsonRender, JsonValue, RenderError};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{hash_str, param, params, register_value_helper, type_name};
+
+/// `{{upper s}}`, `{{lower s}}`, `{{capitalize s}}`, `{{truncate s len=80 suffix="…"}}`, `{{slugify s}}`, `{{replace s from to}}`, `{{trim s}}`, `{{pad s len=8 char=" " side="end"}}`, `{{split s sep=","}}`, `{{join array sep=","}}` and `{{concat a b ...}}`.
+///
+/// Numbers and booleans are treated as their JSON texts and `null` or missing values as empty strings. Arrays and objects are errors, except for the array of `join`.
+///
+/// * `upper` and `lower` use the full Unicode case mappings, so `{{upper "straße"}}` is `STRASSE`.
+/// * `capitalize` uppercases the first character and leaves the rest untouched.
+/// * `truncate` keeps at most `len` grapheme clusters, so emoji and combining marks are never cut in half, and appends `suffix` (`…` by default) if something was cut. The suffix does not count towards `len`.
+/// * `slugify` lowercases the text, keeps Unicode letters and digits, and turns every other run of characters into a single `-`. It does not transliterate, so `{{slugify "Grüße, Welt!"}}` is `grüße-welt`.
+/// * `replace` replaces all occurrences of `from`.
+/// * `trim` removes Unicode whitespace from both ends.
+/// * `pad` pads the text with `char` (a space by default) until it has `len` grapheme clusters. `side` is `"end"` (the default), `"start"` or `"both"`.
+/// * `split` returns an array, so that it can be used with `#each`. `join` renders the items of an array like `{{this}}` would and joins them.
+///
+/// Like any other helper, the results are HTML-escaped by `{{...}}` and left as they are by `{{{...}}}`. The helpers work on the raw texts before escaping, so `truncate` never cuts an HTML entity and `slugify` never sees one.
+pub(super) fn register(handlebars: &mut Handlebars) {
+    register_value_helper(handlebars, "upper", |name, h| {
+        Ok(JsonValue::String(string(name, param(name, h, 0)?)?.to_uppercase()))
+    });
+
+    register_value_helper(handlebars, "lower", |name, h| {
+        Ok(JsonValue::String(string(name, param(name, h, 0)?)?.to_lowercase()))
+    });
+
+    register_value_helper(handlebars, "capitalize", |name, h| {
+        let s = string(name, param(name, h, 0)?)?;
+
+        let mut chars = s.chars();
+
+        let result = match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        };
+
+        Ok(JsonValue::String(result))
+    });
+
+    register_value_helper(handlebars, "truncate", |name, h| {
+        let s = string(name, param(name, h, 0)?)?;
+        let len = len(name, h)?;
+        let suffix = hash_str(name, h, "suffix")?.unwrap_or("…");
+
+        let result = match s.grapheme_indices(true).nth(len) {
+            Some((index, _)) => format!("{}{}", &s[..index], suffix),
+            None => s,
+        };
+
+        Ok(JsonValue::String(result))
+    });
+
+    register_value_helper(handlebars, "slugify", |name, h| {
+        let s = string(name, param(name, h, 0)?)?;
+
+        let mut result = String::with_capacity(s.len());
+
+        for word in s.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()) {
+            if !result.is_empty() {
+                result.push('-');
+            }
+
+            result.extend(word.chars().flat_map(char::to_lowercase));
+        }
+
+        Ok(JsonValue::String(result))
+    });
+
+    register_value_helper(handlebars, "replace", |name, h| {
+        let s = string(name, param(name, h, 0)?)?;
+        let from = string(name, param(name, h, 1)?)?;
+        let to = string(name, param(name, h, 2)?)?;
+
+        if from.is_empty() {
+            return Ok(JsonValue::String(s));
+        }
+
+        Ok(JsonValue::String(s.replace(from.as_str(), to.as_str())))
+    });
+
+    register_value_helper(handlebars, "trim", |name, h| {
+        Ok(JsonValue::String(string(name, param(name, h, 0)?)?.trim().to_string()))
+    });
+
+    register_value_helper(handlebars, "pad", |name, h| {
+        let s = string(name, param(name, h, 0)?)?;
+        let len = len(name, h)?;
+        let c = hash_str(name, h, "char")?.unwrap_or(" ");
+
+        if c.graphemes(true).count() != 1 {
+            return Err(RenderError::new(format!(
+                "The `char` argument of the `{}` helper needs to be a single character.",
+                name
+            )));
+        }
+
+        let missing = len.saturating_sub(s.graphemes(true).count());
+
+        let (start, end) = match hash_str(name, h, "side")?.unwrap_or("end") {
+            "end" => (0, missing),
+            "start" => (missing, 0),
+            "both" => (missing / 2, missing - missing / 2),
+            side => {
+                return Err(RenderError::new(format!(
+                    "The `side` argument of the `{}` helper needs to be \"start\", \"end\" or \
+                     \"both\", but it is {:?}.",
+                    name, side
+                )))
+            },
+        };
+
+        Ok(JsonValue::String(format!("{}{}{}", c.repeat(start), s, c.repeat(end))))
+    });
+
+    register_value_helper(handlebars, "split", |name, h| {
+        let s = string(name, param(name, h, 0)?)?;
+        let sep = hash_str(name, h, "sep")?.unwrap_or(",");
+
+        if s.is_empty() {
+            return Ok(JsonValue::Array(Vec::new()));
+        }
+
+        let items = if sep.is_empty() {
+            s.graphemes(true).map(|g| JsonValue::String(g.to_string())).collect()
+        } else {
+            s.split(sep).map(|item| JsonValue::String(item.to_string())).collect()
+        };
+
+        Ok(JsonValue::Array(items))
+    });
+
+    register_value_helper(handlebars, "join", |name, h| {
+        let sep = hash_str(name, h, "sep")?.unwrap_or(",");
+
+        let result = match param(name, h, 0)? {
+            JsonValue::Array(array) => {
+                array.iter().map(|item| item.render()).collect::<Vec<String>>().join(sep)
+            },
+            JsonValue::Null => String::new(),
+            value => {
+                return Err(RenderError::new(format!(
+                    "The `{}` helper needs an array, but got {}.",
+                    name,
+                    type_name(value)
+                )))
+            },
+        };
+
+        Ok(JsonValue::String(result))
+    });
+
+    register_value_helper(handlebars, "concat", |name, h| {
+        let mut result = String::new();
+
+        for value in params(name, h)? {
+            result.push_str(&string(name, value)?);
+        }
+
+        Ok(JsonValue::String(result))
+    });
+}
+
+/// Convert a string, a number, a boolean or `null` to a string.
+#[inline]
+fn string(name: &str, value: &JsonValue) -> Result<String, RenderError> {
+    match value {
+        JsonValue::String(s) => Ok(s.clone()),
+        JsonValue::Number(_) | JsonValue::Bool(_) => Ok(value.to_string()),
+        JsonValue::Null => Ok(String::new()),
+        _ => Err(RenderError::new(format!(
+            "The `{}` helper needs strings, but got {}.",
+            name,
+            type_name(value)
+        ))),
+    }
+}
+
+/// Get the `len` argument, or the second parameter.
+#[inline]
+fn len(name: &str, h: &Helper) -> Result<usize, RenderError> {
+    let value = match h.hash_get("len") {
+        Some(len) => len.value(),
+        None => param(name, h, 1).map_err(|_| {
+            RenderError::new(format!("The `{}` helper needs the `len` argument.", name))
+        })?,
+    };
+
+    value.as_u64().map(|len| len as usize).ok_or_else(|| {
+        RenderError::new(format!(
+            "The `len` argument of the `{}` helper needs to be a non-negative integer.",
+            name
+        ))
+    })
+}
@@ -0,0 +1,260 @@
+use std::collections::{HashMap, HashSet};
+
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, JsonRender, JsonValue, RenderContext, RenderError,
+    ScopedJson,
+};
+use rocket::{
+    http::{Method, RawStr},
+    Route,
+};
+
+/// A segment of the path of a route.
+#[derive(Debug)]
+enum Segment {
+    Static(String),
+    Dynamic(String),
+    Trailing(String),
+}
+
+/// A field of the query of a route.
+#[derive(Debug)]
+enum Field {
+    Static(String),
+    Dynamic(String),
+}
+
+#[derive(Debug)]
+struct RoutePattern {
+    method: Method,
+    path:   Vec<Segment>,
+    query:  Vec<Field>,
+}
+
+impl RoutePattern {
+    fn new(route: &Route) -> RoutePattern {
+        let path = route
+            .uri
+            .path()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| match dynamic(segment) {
+                Some(name) => match name.strip_suffix("..") {
+                    Some(name) => Segment::Trailing(name.to_string()),
+                    None => Segment::Dynamic(name.to_string()),
+                },
+                None => Segment::Static(segment.to_string()),
+            })
+            .collect();
+
+        let query = route
+            .uri
+            .query()
+            .unwrap_or("")
+            .split('&')
+            .filter(|field| !field.is_empty())
+            .filter_map(|field| match dynamic(field) {
+                // trailing query parameters collect the parameters which are not in the route
+                Some(name) if name.ends_with("..") => None,
+                Some(name) => Some(Field::Dynamic(name.to_string())),
+                None => Some(Field::Static(field.to_string())),
+            })
+            .collect();
+
+        RoutePattern {
+            method: route.method,
+            path,
+            query,
+        }
+    }
+
+    fn build(&self, route_name: &str, h: &Helper) -> Result<String, String> {
+        let mut used = HashSet::new();
+
+        let mut url = String::new();
+
+        for segment in &self.path {
+            url.push('/');
+
+            match segment {
+                Segment::Static(segment) => url.push_str(segment),
+                Segment::Dynamic(name) => {
+                    let value = value(route_name, name, h)?.ok_or_else(|| {
+                        format!("The route `{}` needs the parameter `{}`.", route_name, name)
+                    })?;
+
+                    url.push_str(RawStr::new(&value).percent_encode().as_str());
+
+                    used.insert(name.as_str());
+                },
+                Segment::Trailing(name) => {
+                    let value = value(route_name, name, h)?.unwrap_or_default();
+
+                    let segments = value
+                        .split('/')
+                        .filter(|segment| !segment.is_empty())
+                        .map(|segment| RawStr::new(segment).percent_encode().to_string())
+                        .collect::<Vec<String>>();
+
+                    url.push_str(&segments.join("/"));
+
+                    used.insert(name.as_str());
+                },
+            }
+        }
+
+        if url.is_empty() {
+            url.push('/');
+        }
+
+        let mut query = Vec::new();
+
+        for field in &self.query {
+            match field {
+                Field::Static(field) => query.push(field.clone()),
+                Field::Dynamic(name) => {
+                    // query parameters are optional, so missing ones are just left out
+                    if let Some(value) = value(route_name, name, h)? {
+                        query.push(query_field(name, &value));
+                    }
+
+                    used.insert(name.as_str());
+                },
+            }
+        }
+
+        let mut extra = h
+            .hash()
+            .iter()
+            .filter(|(name, _)| !used.contains(*name))
+            .map(|(name, value)| Ok((*name, scalar(route_name, name, value.value())?)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // the order of the hash is not stable
+        extra.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (name, value) in extra {
+            if let Some(value) = value {
+                query.push(query_field(name, &value));
+            }
+        }
+
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query.join("&"));
+        }
+
+        Ok(url)
+    }
+}
+
+#[inline]
+fn dynamic(s: &str) -> Option<&str> {
+    s.strip_prefix('<').and_then(|s| s.strip_suffix('>'))
+}
+
+#[inline]
+fn query_field(name: &str, value: &str) -> String {
+    format!(
+        "{}={}",
+        RawStr::new(name).percent_encode().as_str(),
+        RawStr::new(value).percent_encode().as_str()
+    )
+}
+
+/// Get a parameter from the hash of the helper. `null` and missing parameters are `None`.
+#[inline]
+fn value(route_name: &str, name: &str, h: &Helper) -> Result<Option<String>, String> {
+    match h.hash_get(name) {
+        Some(value) => scalar(route_name, name, value.value()),
+        None => Ok(None),
+    }
+}
+
+#[inline]
+fn scalar(route_name: &str, name: &str, value: &JsonValue) -> Result<Option<String>, String> {
+    match value {
+        JsonValue::Null => Ok(None),
+        JsonValue::String(_) | JsonValue::Number(_) | JsonValue::Bool(_) => {
+            Ok(Some(value.render()))
+        },
+        _ => Err(format!(
+            "The parameter `{}` of the route `{}` needs to be a string, a number or a boolean.",
+            name, route_name
+        )),
+    }
+}
+
+/// The `url` helper, which builds the URL of a named route.
+///
+/// `{{url "user_profile" id=42}}` looks up the route whose handler is `user_profile`, fills its dynamic path segments and query parameters with the hash, and appends the rest of the hash as query parameters. Values are percent-encoded. If several routes have the same name, the `GET` one is used.
+///
+/// Unknown routes and missing path parameters are errors in release builds. In debug builds, they are logged as warnings and the URL is `#`, so that the page can still be rendered.
+#[derive(Debug)]
+pub(crate) struct UrlHelper {
+    routes: HashMap<String, RoutePattern>,
+}
+
+impl UrlHelper {
+    /// Collect the named routes.
+    pub(crate) fn new<'a, I: Iterator<Item = &'a Route>>(routes: I) -> UrlHelper {
+        let mut patterns: HashMap<String, RoutePattern> = HashMap::new();
+
+        for route in routes {
+            let name = match route.name.as_ref() {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let replace = match patterns.get(name.as_ref()) {
+                Some(pattern) => pattern.method != Method::Get && route.method == Method::Get,
+                None => true,
+            };
+
+            if replace {
+                patterns.insert(name.to_string(), RoutePattern::new(route));
+            }
+        }
+
+        UrlHelper {
+            routes: patterns
+        }
+    }
+
+    fn build(&self, h: &Helper) -> Result<String, String> {
+        let name = h.param(0).and_then(|param| param.value().as_str()).ok_or_else(|| {
+            String::from("The `url` helper needs a route name as its first parameter.")
+        })?;
+
+        match self.routes.get(name) {
+            Some(pattern) => pattern.build(name, h),
+            None => Err(format!("The route `{}` does not exist.", name)),
+        }
+    }
+}
+
+impl HelperDef for UrlHelper {
+    #[inline]
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let url = match self.build(h) {
+            Ok(url) => url,
+            Err(message) => {
+                if cfg!(debug_assertions) {
+                    rocket::warn_!("{}", message);
+
+                    String::from("#")
+                } else {
+                    return Err(RenderError::new(message));
+                }
+            },
+        };
+
+        Ok(ScopedJson::Derived(JsonValue::String(url)))
+    }
+}
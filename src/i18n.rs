@@ -0,0 +1,390 @@
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::HashMap,
+    fs, io,
+    path::PathBuf,
+    sync::{Arc, PoisonError, RwLock},
+    time::SystemTime,
+};
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, JsonValue, RenderContext, RenderError, ScopedJson,
+};
+use unic_langid::LanguageIdentifier;
+
+use crate::Locale;
+
+thread_local! {
+    static REGISTERED_TRANSLATIONS: RefCell<Option<Arc<Translations>>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug)]
+enum Source {
+    Embedded(&'static str),
+    File(PathBuf),
+}
+
+struct Bundles {
+    bundles: HashMap<LanguageIdentifier, FluentBundle<FluentResource>>,
+    mtimes:  Vec<Option<SystemTime>>,
+}
+
+/// Fluent message bundles for the `t` and `locale` helpers. Use the `handlebars_translations_initialize!` macro in the fairing to register `.ftl` files, so that they are embedded in release builds and read again whenever they are modified in debug builds.
+///
+/// A message is looked up in the locales negotiated from the `Locale` of a render, then in the default locale. For example, a client preferring `de-AT` gets `de-AT`, then `de`, then the default locale.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct Translations {
+    default: LanguageIdentifier,
+    sources: Vec<(LanguageIdentifier, Source)>,
+    #[educe(Debug(ignore))]
+    bundles: RwLock<Bundles>,
+}
+
+impl Translations {
+    /// Create an instance of `Translations` with the default locale, like `en-US`.
+    #[inline]
+    pub fn new<S: AsRef<str>>(default_locale: S) -> Result<Translations, io::Error> {
+        Ok(Translations {
+            default: parse_locale(default_locale.as_ref())?,
+            sources: Vec::new(),
+            bundles: RwLock::new(Bundles {
+                bundles: HashMap::new(), mtimes: Vec::new()
+            }),
+        })
+    }
+
+    /// Add a Fluent resource which is embedded in the executable binary file to a locale.
+    #[inline]
+    pub fn insert_str<S: AsRef<str>>(
+        &mut self,
+        locale: S,
+        resource: &'static str,
+    ) -> Result<(), io::Error> {
+        self.insert(locale.as_ref(), Source::Embedded(resource))
+    }
+
+    /// Add a Fluent resource from a path to a locale and it can be reloaded automatically.
+    #[inline]
+    pub fn insert_file<S: AsRef<str>, P: Into<PathBuf>>(
+        &mut self,
+        locale: S,
+        file_path: P,
+    ) -> Result<(), io::Error> {
+        self.insert(locale.as_ref(), Source::File(file_path.into()))
+    }
+
+    fn insert(&mut self, locale: &str, source: Source) -> Result<(), io::Error> {
+        self.sources.push((parse_locale(locale)?, source));
+
+        match load(&self.sources) {
+            Ok(bundles) => {
+                *self.bundles.get_mut().unwrap_or_else(PoisonError::into_inner) = bundles;
+
+                Ok(())
+            },
+            Err(err) => {
+                self.sources.pop();
+
+                Err(err)
+            },
+        }
+    }
+
+    /// Read the resources again if any of their files has been modified.
+    pub fn reload_if_needed(&self) -> Result<(), io::Error> {
+        let modified = {
+            let bundles = self.bundles.read().unwrap_or_else(PoisonError::into_inner);
+
+            self.sources.iter().zip(bundles.mtimes.iter()).any(
+                |((_, source), mtime)| match source {
+                    Source::Embedded(_) => false,
+                    Source::File(file_path) => {
+                        match (mtime, file_path.metadata().and_then(|m| m.modified()).ok()) {
+                            (Some(old), Some(new)) => new > *old,
+                            _ => true,
+                        }
+                    },
+                },
+            )
+        };
+
+        if modified {
+            *self.bundles.write().unwrap_or_else(PoisonError::into_inner) = load(&self.sources)?;
+        }
+
+        Ok(())
+    }
+
+    /// The default locale.
+    #[inline]
+    pub fn default_locale(&self) -> &LanguageIdentifier {
+        &self.default
+    }
+
+    /// Negotiate the available locales with a `Locale`, in the order they are looked up. The default locale is always included.
+    pub fn negotiate(&self, locale: &Locale) -> Vec<LanguageIdentifier> {
+        let requested = locale
+            .tags()
+            .iter()
+            .filter_map(|tag| tag.parse::<LanguageIdentifier>().ok())
+            .collect::<Vec<_>>();
+
+        let mut available: Vec<LanguageIdentifier> = Vec::new();
+
+        for (locale, _) in &self.sources {
+            if !available.contains(locale) {
+                available.push(locale.clone());
+            }
+        }
+
+        // the default locale is appended if it is not negotiated
+        negotiate_languages(
+            &requested,
+            &available,
+            Some(&self.default),
+            NegotiationStrategy::Filtering,
+        )
+        .into_iter()
+        .cloned()
+        .collect()
+    }
+
+    /// Get the first negotiated locale for a `Locale`, which is returned by the `locale` helper.
+    #[inline]
+    pub fn negotiated_locale(&self, locale: &Locale) -> LanguageIdentifier {
+        // the default locale is always negotiated
+        self.negotiate(locale).swap_remove(0)
+    }
+
+    /// Format a message for a `Locale`. `id` can be `message` or `message.attribute`. Return `None` if the message does not exist in any of the negotiated locales.
+    pub fn format(&self, locale: &Locale, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+        let (id, attribute) = match id.split_once('.') {
+            Some((id, attribute)) => (id, Some(attribute)),
+            None => (id, None),
+        };
+
+        let bundles = self.bundles.read().unwrap_or_else(PoisonError::into_inner);
+
+        for locale in self.negotiate(locale) {
+            let bundle = match bundles.bundles.get(&locale) {
+                Some(bundle) => bundle,
+                None => continue,
+            };
+
+            let message = match bundle.get_message(id) {
+                Some(message) => message,
+                None => continue,
+            };
+
+            let pattern = match attribute {
+                Some(attribute) => message.get_attribute(attribute).map(|a| a.value()),
+                None => message.value(),
+            };
+
+            if let Some(pattern) = pattern {
+                let mut errors = Vec::new();
+
+                let value = bundle.format_pattern(pattern, args, &mut errors);
+
+                // missing arguments are rendered like `{$name}` by Fluent, so it is only worth a warning
+                if cfg!(debug_assertions) {
+                    for err in errors {
+                        rocket::warn_!("The message `{}` of the locale `{}`: {}", id, locale, err);
+                    }
+                }
+
+                return Some(value.into_owned());
+            }
+        }
+
+        None
+    }
+}
+
+#[inline]
+fn parse_locale(locale: &str) -> Result<LanguageIdentifier, io::Error> {
+    locale.parse().map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("`{}` is not a valid locale: {}", locale, err),
+        )
+    })
+}
+
+fn load(sources: &[(LanguageIdentifier, Source)]) -> Result<Bundles, io::Error> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let mut bundles: HashMap<LanguageIdentifier, FluentBundle<FluentResource>> = HashMap::new();
+    let mut mtimes = Vec::with_capacity(sources.len());
+
+    for (locale, source) in sources {
+        let (content, name, mtime) = match source {
+            Source::Embedded(content) => {
+                (Cow::Borrowed(*content), Cow::Borrowed("<embedded>"), None)
+            },
+            Source::File(file_path) => {
+                let mtime = file_path.metadata()?.modified().ok();
+
+                (
+                    Cow::Owned(fs::read_to_string(file_path)?),
+                    Cow::Owned(file_path.to_string_lossy().into_owned()),
+                    mtime,
+                )
+            },
+        };
+
+        let resource = FluentResource::try_new(content.into_owned()).map_err(|(_, errors)| {
+            invalid(format!(
+                "The Fluent resource {} of the locale `{}` cannot be parsed: {}",
+                name,
+                locale,
+                errors.iter().map(|err| err.to_string()).collect::<Vec<_>>().join("; ")
+            ))
+        })?;
+
+        let bundle = bundles.entry(locale.clone()).or_insert_with(|| {
+            let mut bundle = FluentBundle::new_concurrent(vec![locale.clone()]);
+
+            // Unicode isolation marks are not wanted in HTML
+            bundle.set_use_isolating(false);
+
+            bundle
+        });
+
+        bundle.add_resource(resource).map_err(|errors| {
+            invalid(format!(
+                "The Fluent resource {} of the locale `{}` cannot be added: {}",
+                name,
+                locale,
+                errors.iter().map(|err| err.to_string()).collect::<Vec<_>>().join("; ")
+            ))
+        })?;
+
+        mtimes.push(mtime);
+    }
+
+    Ok(Bundles {
+        bundles,
+        mtimes,
+    })
+}
+
+/// Register `{{t "welcome-user" name=user.name}}` and `{{locale}}` with translations. Use the `handlebars_translations_initialize!` macro in the fairing instead of calling this function directly.
+///
+/// * `t` formats a Fluent message with the hash as its arguments, in the locale of the render, which is set by `HandlebarsContextManager::build_localized`. Numbers can select plural categories, like `{ $count -> [one] ... *[other] ... }`, and strings and booleans can be used in selectors. A message which does not exist in any locale is an error in release builds, and is logged as a warning and rendered as its ID in debug builds.
+/// * `locale` returns the first negotiated locale, like `de-AT`, which can be used in `<html lang="...">`.
+pub fn register_translation_helpers(handlebars: &mut Handlebars, translations: Translations) {
+    let translations = Arc::new(translations);
+
+    REGISTERED_TRANSLATIONS
+        .with(|registered| *registered.borrow_mut() = Some(translations.clone()));
+
+    handlebars.register_helper(
+        "t",
+        Box::new(TranslateHelper {
+            translations: translations.clone()
+        }),
+    );
+
+    handlebars.register_helper(
+        "locale",
+        Box::new(LocaleHelper {
+            translations,
+        }),
+    );
+}
+
+/// Take the translations registered by `register_translation_helpers` in this thread, so that `HandlebarsContextManager` can key caches on the negotiated locale.
+#[inline]
+pub(crate) fn take_registered_translations() -> Option<Arc<Translations>> {
+    REGISTERED_TRANSLATIONS.with(|registered| registered.borrow_mut().take())
+}
+
+struct TranslateHelper {
+    translations: Arc<Translations>,
+}
+
+impl HelperDef for TranslateHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let id = h.param(0).and_then(|param| param.value().as_str()).ok_or_else(|| {
+            RenderError::new("The `t` helper needs the ID of a message as its first parameter.")
+        })?;
+
+        self.translations
+            .reload_if_needed()
+            .map_err(|err| RenderError::new(format!("The translations cannot be read: {}", err)))?;
+
+        let mut args = FluentArgs::new();
+
+        for (name, value) in h.hash() {
+            let value = match value.value() {
+                JsonValue::Null => continue,
+                JsonValue::String(s) => FluentValue::from(s.clone()),
+                JsonValue::Number(n) => match n.as_i64() {
+                    Some(n) => FluentValue::from(n),
+                    None => FluentValue::from(n.as_f64().unwrap_or_default()),
+                },
+                JsonValue::Bool(b) => FluentValue::from(if *b { "true" } else { "false" }),
+                _ => {
+                    return Err(RenderError::new(format!(
+                        "The argument `{}` of the `t` helper needs to be a string, a number or a \
+                         boolean.",
+                        name
+                    )))
+                },
+            };
+
+            args.set(*name, value);
+        }
+
+        let locale = Locale::current().unwrap_or_default();
+
+        let text = match self.translations.format(&locale, id, Some(&args)) {
+            Some(text) => text,
+            None => {
+                let message = format!("The message `{}` does not exist.", id);
+
+                if cfg!(debug_assertions) {
+                    rocket::warn_!("{}", message);
+
+                    id.to_string()
+                } else {
+                    return Err(RenderError::new(message));
+                }
+            },
+        };
+
+        Ok(ScopedJson::Derived(JsonValue::String(text)))
+    }
+}
+
+struct LocaleHelper {
+    translations: Arc<Translations>,
+}
+
+impl HelperDef for LocaleHelper {
+    #[inline]
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        _: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let locale = Locale::current().unwrap_or_default();
+
+        Ok(ScopedJson::Derived(JsonValue::String(
+            self.translations.negotiated_locale(&locale).to_string(),
+        )))
+    }
+}
@@ -3,10 +3,17 @@
 
 This is a crate which provides macros `handlebars_resources_initialize!` and `handlebars_response!` to statically include HBS (Handlebars) files from your Rust project and make them be the HTTP response sources quickly.
 
-* `handlebars_resources_initialize!` is used in the fairing of `HandlebarsResponse` to include Handlebars files into your executable binary file. You need to specify each file's name and its path relative to the directory containing the manifest of your package. In order to reduce the compilation time and allow to hot-reload templates, files are compiled into your executable binary file together, only when you are using the **release** profile.
+* `handlebars_resources_initialize!` is used in the fairing of `HandlebarsResponse` to include Handlebars files into your executable binary file. You need to specify each file's name and its path relative to the directory containing the manifest of your package. In order to reduce the compilation time and allow to hot-reload templates, files are compiled into your executable binary file together, only when you are using the **release** profile. A file named like `terms.de.hbs` is registered as the `de` variant of `terms`, or a name like `terms@de` can be given explicitly, and `HandlebarsContextManager::build_localized` renders `terms.de-AT`, `terms.de` or `terms` for a `Locale`, whichever exists first. `handlebars_response_cache!(localized ...)` builds the response in the same way and keys its cache on the resolved variant.
 * `handlebars_response!` is used for retrieving and rendering the file you input through the macro `handlebars_resources_initialize!` as a `HandlebarsResponse` instance with rendered HTML. When its `respond_to` method is called, three HTTP headers, **Content-Type**, **Content-Length** and **Etag**, will be automatically added, and the rendered HTML can optionally not be minified.
-* `handlebars_response_cache!` is used for wrapping a `HandlebarsResponse` and its constructor, and use a **key** to cache its HTML and ETag in memory. The cache is generated only when you are using the **release** profile.
+* `handlebars_response_cache!` is used for wrapping a `HandlebarsResponse` and its constructor, and use a **key** to cache its HTML and ETag in memory. The cache is generated only when you are using the **release** profile, unless the `debug_cache` feature is enabled.
 * `handlebars_resources_initializer!` is used for generating a fairing for handlebars resources.
+* The fairing registers a `cache` block helper. `{{#cache "sidebar" ttl=300}}...{{/cache}}` caches the rendered block by the key `fragment:sidebar` for 300 seconds, so a part of a page can be reused by different pages. Like `handlebars_response_cache!`, it only caches in the **release** profile, unless the `debug_cache` feature is enabled.
+* The fairing also registers a `url` helper with the named routes of Rocket. `{{url "user_profile" id=42}}` becomes the percent-encoded URL of the route whose handler is `user_profile`, like `/users/42`, and the parameters which are not in the route are appended as its query. Unknown routes and missing parameters are errors in the **release** profile, and they are logged as warnings and become `#` otherwise.
+* `handlebars_asset_manifest!` is used in the fairing of `HandlebarsResponse` to register the `asset`, `asset_integrity` and `asset_preload` helpers with the asset manifest emitted by your frontend build, e.g. `{{asset "app.js"}}` becomes `/static/app.3f9a1c.js`. Like templates, the manifest is compiled into your executable binary file only when you are using the **release** profile, and it is read again whenever it is modified otherwise.
+* `handlebars_files_initialize!` is used in the fairing of `HandlebarsResponse` to include files for the `sri` and `include_raw` helpers, with the `helper_file` feature. `{{sri "static/app.js"}}` becomes the SRI hash of the file and `{{include_raw "static/icons/logo.svg"}}` inlines the file. Like templates, the files are compiled into your executable binary file only when you are using the **release** profile, and they are read again whenever they are modified otherwise.
+* `handlebars_translations_initialize!` is used in the fairing of `HandlebarsResponse` to register Fluent `.ftl` resources for the `t` and `locale` helpers, with the `i18n` feature. `{{t "welcome-user" name=user.name}}` formats a message in the locale given to `HandlebarsContextManager::build_localized`, which can be the `Locale` request guard, read from the `lang` cookie or the `Accept-Language` header. Messages fall back from `de-AT` to `de` and then to the default locale. Like templates, the resources are compiled into your executable binary file only when you are using the **release** profile, and they are read again whenever they are modified otherwise.
+* The `helper_*` features make the fairing of `HandlebarsResponse` register the helpers of this crate, and the `helpers` feature enables all of them except `helper_compare`, `helper_logic`, `helper_date`, `helper_markdown`, `helper_sanitize` and `helper_highlight`. `helper_compare` and `helper_logic` replace the built-in `eq`, `ne`, `gt`, `gte`, `lt`, `lte`, `and`, `or` and `not` of Handlebars with stricter ones, e.g. comparing a number with a string is an error, so existing templates do not change unless they are opted in. The other four pull in `chrono`, `pulldown-cmark`, `ammonia` and `syntect`. Each of them needs to be opted in separately, e.g. `features = ["helpers", "helper_compare", "helper_markdown"]`. The `len` helper of `helper_collection` replaces the built-in one of Handlebars and counts the grapheme clusters of a string instead of its bytes.
+* **Breaking change:** in the **release** profile, the `handlebars` field of `HandlebarsContextManager` is replaced by the `handlebars` method, which returns a read guard of the registered templates and helpers, e.g. `cm.handlebars().has_template("index")`. Templates are changed by `register_template_file`, `register_template_string` and `unregister_template`, which drop the caches rendered from them.
 
 See `examples`.
 */
@@ -17,7 +24,12 @@ extern crate educe;
 #[doc(hidden)]
 pub extern crate manifest_dir_macros;
 
+mod cache;
 mod functions;
+mod helpers;
+#[cfg(feature = "i18n")]
+mod i18n;
+mod locale;
 
 #[cfg(debug_assertions)]
 mod debug;
@@ -27,9 +39,20 @@ mod release;
 
 mod macros;
 
+#[cfg(feature = "helper_sanitize")]
+pub use ammonia;
+pub use cache::*;
+#[cfg(feature = "helper_date")]
+pub use chrono;
 #[cfg(debug_assertions)]
 pub use debug::*;
+#[cfg(feature = "i18n")]
+pub use fluent_bundle;
 pub use handlebars::handlebars_helper;
+pub use helpers::*;
+#[cfg(feature = "i18n")]
+pub use i18n::*;
+pub use locale::{template_variant_name, Locale};
 #[cfg(not(debug_assertions))]
 pub use release::*;
 pub use rocket_etag_if_none_match::{entity_tag::EntityTag, EtagIfNoneMatch};
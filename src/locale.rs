@@ -0,0 +1,266 @@
+use std::{borrow::Cow, cell::RefCell, path::Path};
+
+use rocket::{
+    request::{FromRequest, Outcome},
+    Request,
+};
+
+#[cfg(all(feature = "i18n", any(not(debug_assertions), feature = "debug_cache")))]
+use crate::Translations;
+
+thread_local! {
+    static CURRENT_LOCALE: RefCell<Option<Locale>> = const { RefCell::new(None) };
+}
+
+/// The locales preferred by a client, in order of preference. Language tags are normalized like `de-AT` and `zh-Hant-TW`.
+///
+/// As a request guard, the locale is read from the `lang` cookie first, and then from the `Accept-Language` header. It never fails, and a client which prefers nothing gets an empty `Locale`, for which the default locale is used.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Locale {
+    tags: Vec<String>,
+}
+
+impl Locale {
+    /// The name of the cookie which overrides the `Accept-Language` header.
+    pub const COOKIE_NAME: &'static str = "lang";
+
+    /// Create a `Locale` with only one language tag. An invalid tag results in an empty `Locale`.
+    #[inline]
+    pub fn new<S: AsRef<str>>(tag: S) -> Locale {
+        Locale::from_tags([tag])
+    }
+
+    /// Create a `Locale` with language tags in order of preference. Invalid tags are ignored.
+    #[inline]
+    pub fn from_tags<I: IntoIterator<Item = S>, S: AsRef<str>>(tags: I) -> Locale {
+        let mut locale = Locale::default();
+
+        for tag in tags {
+            if let Some(tag) = normalize(tag.as_ref()) {
+                if !locale.tags.contains(&tag) {
+                    locale.tags.push(tag);
+                }
+            }
+        }
+
+        locale
+    }
+
+    /// Parse the value of an `Accept-Language` header, like `de-AT, de;q=0.9, en;q=0.5`. The tags are sorted by their quality values, and `*` and tags with `q=0` are ignored.
+    pub fn from_accept_language<S: AsRef<str>>(header: S) -> Locale {
+        let mut tags = header
+            .as_ref()
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.split(';');
+
+                let tag = parts.next()?.trim();
+
+                let mut quality = 1.0;
+
+                for param in parts {
+                    if let Some((key, value)) = param.split_once('=') {
+                        if key.trim().eq_ignore_ascii_case("q") {
+                            quality = value.trim().parse::<f32>().ok()?;
+                        }
+                    }
+                }
+
+                if quality > 0.0 {
+                    Some((tag, quality))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // the sort is stable, so the order of tags with the same quality is kept
+        tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        Locale::from_tags(tags.into_iter().map(|(tag, _)| tag))
+    }
+
+    /// The language tags in order of preference.
+    #[inline]
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Check if there is no language tag.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// The language tags followed by their more general tags, like `de-AT`, `de`, `en-US`, `en`, without duplicates.
+    pub fn fallbacks(&self) -> Vec<String> {
+        let mut fallbacks: Vec<String> = Vec::new();
+
+        for tag in &self.tags {
+            let mut tag = tag.as_str();
+
+            loop {
+                if !fallbacks.iter().any(|fallback| fallback == tag) {
+                    fallbacks.push(tag.to_string());
+                }
+
+                match tag.rfind('-') {
+                    Some(index) => tag = &tag[..index],
+                    None => break,
+                }
+            }
+        }
+
+        fallbacks
+    }
+
+    /// Run a function with this locale as the locale of the templates rendered in it.
+    #[inline]
+    pub(crate) fn scope<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        struct Restore(Option<Locale>);
+
+        impl Drop for Restore {
+            #[inline]
+            fn drop(&mut self) {
+                let previous = self.0.take();
+
+                CURRENT_LOCALE.with(|current| *current.borrow_mut() = previous);
+            }
+        }
+
+        let _restore =
+            Restore(CURRENT_LOCALE.with(|current| current.borrow_mut().replace(self.clone())));
+
+        f()
+    }
+
+    /// Get the part of cache keys which depends on this locale, for a render of a template which may be a variant resolved by `resolve_template`. It is the language tag of the variant, like `de` for `terms.de`, followed by the locale negotiated by the translations of the `t` and `locale` helpers with the `i18n` feature, so it only varies among the registered variants and the available translations, however many locales clients send. Return `None` if there is neither.
+    #[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+    pub(crate) fn cache_key_suffix(
+        &self,
+        template: &str,
+        #[cfg(feature = "i18n")] translations: Option<&Translations>,
+    ) -> Option<String> {
+        let mut parts = Vec::with_capacity(2);
+
+        if let Some(tag) = self.fallbacks().into_iter().find(|tag| {
+            template.strip_suffix(tag.as_str()).map(|name| name.ends_with('.')).unwrap_or(false)
+        }) {
+            parts.push(tag);
+        }
+
+        #[cfg(feature = "i18n")]
+        if let Some(translations) = translations {
+            parts.push(translations.negotiated_locale(self).to_string());
+        }
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(","))
+        }
+    }
+
+    /// The locale of the templates which are being rendered in this thread, if any.
+    #[cfg(any(feature = "i18n", not(debug_assertions), feature = "debug_cache"))]
+    #[inline]
+    pub(crate) fn current() -> Option<Locale> {
+        CURRENT_LOCALE.with(|current| current.borrow().clone())
+    }
+}
+
+/// Get the name of a template for its file. A file named like `terms.de-AT.hbs` registered as `terms` is the `de-AT` variant of `terms`, so it is named `terms.de-AT`. A name like `terms@de-AT` is the `de-AT` variant of `terms` whatever its file is named. Return `None` if the language tag after `@` is invalid.
+#[doc(hidden)]
+pub fn template_variant_name<'a>(name: &'a str, file_path: &str) -> Option<Cow<'a, str>> {
+    if let Some((base, tag)) = name.rsplit_once('@') {
+        return match normalize(tag) {
+            Some(tag) if !base.is_empty() => Some(Cow::Owned(format!("{}.{}", base, tag))),
+            _ => None,
+        };
+    }
+
+    let file_name = Path::new(file_path).file_name().and_then(|name| name.to_str()).unwrap_or("");
+
+    let tag = file_name
+        .rsplit_once('.')
+        .and_then(|(stem, _)| stem.rsplit_once('.'))
+        // a name like `legal/terms` matches a file named like `terms.de.hbs`
+        .filter(|(base, _)| name.rsplit('/').next() == Some(*base))
+        .map(|(_, tag)| tag);
+
+    // only two-letter languages, so that files like `index.min.hbs` and `page.old.hbs` are not mistaken for variants
+    match tag
+        .filter(|tag| tag.split(['-', '_']).next().map(str::len) == Some(2))
+        .and_then(normalize)
+    {
+        Some(tag) => Some(Cow::Owned(format!("{}.{}", name, tag))),
+        None => Some(Cow::Borrowed(name)),
+    }
+}
+
+/// Normalize a language tag, like `de-at` to `de-AT`. The language is lowercased, a four-letter script is titlecased and a two-letter region is uppercased.
+fn normalize(tag: &str) -> Option<String> {
+    let tag = tag.trim();
+
+    if tag.is_empty() || tag == "*" {
+        return None;
+    }
+
+    let mut normalized = String::with_capacity(tag.len());
+
+    for (index, subtag) in tag.split(['-', '_']).enumerate() {
+        if subtag.is_empty()
+            || subtag.len() > 8
+            || !subtag.bytes().all(|b| b.is_ascii_alphanumeric())
+        {
+            return None;
+        }
+
+        if index == 0 {
+            if !subtag.bytes().all(|b| b.is_ascii_alphabetic()) {
+                return None;
+            }
+
+            normalized.push_str(&subtag.to_ascii_lowercase());
+
+            continue;
+        }
+
+        normalized.push('-');
+
+        if subtag.len() == 4 && subtag.bytes().all(|b| b.is_ascii_alphabetic()) {
+            normalized.push_str(&subtag[..1].to_ascii_uppercase());
+            normalized.push_str(&subtag[1..].to_ascii_lowercase());
+        } else if subtag.len() == 2 && subtag.bytes().all(|b| b.is_ascii_alphabetic()) {
+            normalized.push_str(&subtag.to_ascii_uppercase());
+        } else {
+            normalized.push_str(&subtag.to_ascii_lowercase());
+        }
+    }
+
+    Some(normalized)
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Locale {
+    type Error = ();
+
+    #[inline]
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        if let Some(cookie) = request.cookies().get(Locale::COOKIE_NAME) {
+            let locale = Locale::new(cookie.value());
+
+            if !locale.is_empty() {
+                return Outcome::Success(locale);
+            }
+        }
+
+        Outcome::Success(
+            request
+                .headers()
+                .get_one("Accept-Language")
+                .map(Locale::from_accept_language)
+                .unwrap_or_default(),
+        )
+    }
+}
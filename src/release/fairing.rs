@@ -1,17 +1,22 @@
+use std::{path::PathBuf, sync::PoisonError};
+
 use handlebars::Handlebars;
 use rocket::{
     fairing::{Fairing, Info, Kind},
-    Build, Rocket,
+    Build, Orbit, Rocket,
 };
 
 use super::{HandlebarsContextManager, HandlebarsResponse};
-use crate::functions::add_helpers;
+use crate::{functions::add_helpers, ResponseCache, ShardedResponseCache, UrlHelper};
 
 const FAIRING_NAME: &str = "Handlebars";
+const SNAPSHOT_FAIRING_NAME: &str = "Handlebars Cache Snapshot";
 
 /// The fairing of `HandlebarsResponse`.
 pub struct HandlebarsResponseFairing {
-    pub(crate) custom_callback: Box<dyn Fn(&mut Handlebars) -> usize + Send + Sync + 'static>,
+    #[allow(clippy::type_complexity)]
+    pub(crate) custom_callback:
+        Box<dyn Fn(&mut Handlebars) -> Box<dyn ResponseCache> + Send + Sync + 'static>,
 }
 
 #[rocket::async_trait]
@@ -29,14 +34,78 @@ impl Fairing for HandlebarsResponseFairing {
 
         add_helpers(&mut handlebars);
 
-        let cache_capacity = (self.custom_callback)(&mut handlebars);
+        handlebars.register_helper("url", Box::new(UrlHelper::new(rocket.routes())));
+
+        // drop translations registered out of a fairing, so that only the ones registered in the callback are taken by `HandlebarsContextManager::new`
+        #[cfg(feature = "i18n")]
+        crate::i18n::take_registered_translations();
+
+        let cache_table = (self.custom_callback)(&mut handlebars);
+
+        let mut state = HandlebarsContextManager::new(handlebars, cache_table);
+
+        let fragment_cache_helper = state.fragment_cache_helper();
 
-        let state = HandlebarsContextManager::new(handlebars, cache_capacity);
+        state
+            .handlebars
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .register_helper("cache", Box::new(fragment_cache_helper));
 
         Ok(rocket.manage(state))
     }
 }
 
+/// The fairing which restores the cache of `HandlebarsContextManager` from a file at ignite and saves the cache to the file on shutdown.
+pub struct HandlebarsCacheSnapshotFairing {
+    pub(crate) path: PathBuf,
+}
+
+#[rocket::async_trait]
+impl Fairing for HandlebarsCacheSnapshotFairing {
+    #[inline]
+    fn info(&self) -> Info {
+        Info {
+            name: SNAPSHOT_FAIRING_NAME, kind: Kind::Ignite | Kind::Shutdown
+        }
+    }
+
+    #[inline]
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> Result<Rocket<Build>, Rocket<Build>> {
+        let cm = match rocket.state::<HandlebarsContextManager>() {
+            Some(cm) => cm,
+            None => {
+                rocket::error_!(
+                    "The fairing of `HandlebarsResponse` needs to be attached before the cache \
+                     snapshot fairing."
+                );
+
+                return Err(rocket);
+            },
+        };
+
+        match cm.load_cache_snapshot(&self.path) {
+            Ok(count) => {
+                rocket::info_!("{} cache(s) restored from {:?}.", count, self.path);
+            },
+            Err(err) => {
+                rocket::warn_!("Cannot restore caches from {:?}: {}", self.path, err);
+            },
+        }
+
+        Ok(rocket)
+    }
+
+    #[inline]
+    async fn on_shutdown(&self, rocket: &Rocket<Orbit>) {
+        if let Some(cm) = rocket.state::<HandlebarsContextManager>() {
+            if let Err(err) = cm.save_cache_snapshot(&self.path) {
+                rocket::warn_!("Cannot save caches to {:?}: {}", self.path, err);
+            }
+        }
+    }
+}
+
 impl HandlebarsResponse {
     /// Create the fairing of `HandlebarsResponse`.
     #[inline]
@@ -49,7 +118,7 @@ impl HandlebarsResponse {
             custom_callback: Box::new(move |handlebars| {
                 f(handlebars);
 
-                crate::DEFAULT_CACHE_CAPACITY
+                Box::new(ShardedResponseCache::new(crate::DEFAULT_CACHE_CAPACITY))
             }),
         }
     }
@@ -60,7 +129,28 @@ impl HandlebarsResponse {
     where
         F: Fn(&mut Handlebars) -> usize + Send + Sync + 'static, {
         HandlebarsResponseFairing {
-            custom_callback: Box::new(f)
+            custom_callback: Box::new(move |handlebars| {
+                Box::new(ShardedResponseCache::new(f(handlebars)))
+            }),
+        }
+    }
+
+    /// Create the fairing of `HandlebarsResponse` and set the storage of the cache.
+    #[inline]
+    pub fn fairing_custom_cache<F, C>(f: F) -> impl Fairing
+    where
+        F: Fn(&mut Handlebars) -> C + Send + Sync + 'static,
+        C: ResponseCache + 'static, {
+        HandlebarsResponseFairing {
+            custom_callback: Box::new(move |handlebars| Box::new(f(handlebars))),
+        }
+    }
+
+    /// Create the fairing which restores the cache from a file at ignite and saves the cache to the file on shutdown. It needs to be attached after the fairing of `HandlebarsResponse`.
+    #[inline]
+    pub fn cache_snapshot_fairing<P: Into<PathBuf>>(path: P) -> impl Fairing {
+        HandlebarsCacheSnapshotFairing {
+            path: path.into()
         }
     }
 }
@@ -7,7 +7,7 @@ use rocket::{
     response::{self, Responder, Response},
 };
 
-use crate::EntityTag;
+use crate::{EntityTag, TemplateVersion};
 
 #[derive(Debug)]
 enum HandlebarsResponseInner {
@@ -18,7 +18,10 @@ enum HandlebarsResponseInner {
 #[derive(Debug)]
 /// To respond HTML.
 pub struct HandlebarsResponse {
-    inner: Option<HandlebarsResponseInner>,
+    inner:                Option<HandlebarsResponseInner>,
+    vary:                 Option<String>,
+    /// The templates which the HTML is rendered from.
+    pub(crate) templates: Option<Arc<[TemplateVersion]>>,
 }
 
 impl HandlebarsResponse {
@@ -28,10 +31,12 @@ impl HandlebarsResponse {
         etag: &EntityTag<'static>,
     ) -> HandlebarsResponse {
         HandlebarsResponse {
-            inner: Some(HandlebarsResponseInner::NotCache {
+            inner:     Some(HandlebarsResponseInner::NotCache {
                 content: content.into(),
                 etag:    etag.to_string(),
             }),
+            vary:      None,
+            templates: None,
         }
     }
 
@@ -39,21 +44,47 @@ impl HandlebarsResponse {
     #[inline]
     pub fn build_cache(content: Arc<str>, etag: &EntityTag<'static>) -> HandlebarsResponse {
         HandlebarsResponse {
-            inner: Some(HandlebarsResponseInner::Cache {
+            inner:     Some(HandlebarsResponseInner::Cache {
                 content,
                 etag: etag.to_string(),
             }),
+            vary:      None,
+            templates: None,
         }
     }
 
+    #[inline]
+    pub(crate) fn with_templates(
+        mut self,
+        templates: Option<Arc<[TemplateVersion]>>,
+    ) -> HandlebarsResponse {
+        self.templates = templates;
+
+        self
+    }
+
+    /// Set the **Vary** header of this response.
+    #[inline]
+    pub fn vary<S: Into<String>>(mut self, vary: S) -> HandlebarsResponse {
+        self.vary = Some(vary.into());
+
+        self
+    }
+
     #[doc(hidden)]
     #[inline]
     pub const fn not_modified() -> HandlebarsResponse {
         HandlebarsResponse {
-            inner: None
+            inner: None, vary: None, templates: None
         }
     }
 
+    /// Check if this response is a **304 Not Modified** response.
+    #[inline]
+    pub const fn is_not_modified(&self) -> bool {
+        self.inner.is_none()
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn into_html_and_etag(self) -> Option<(Arc<str>, EntityTag<'static>)> {
@@ -90,6 +121,10 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for HandlebarsResponse {
     fn respond_to(self, _: &'r Request<'_>) -> response::Result<'o> {
         let mut response = Response::build();
 
+        if let Some(vary) = self.vary {
+            response.raw_header("Vary", vary);
+        }
+
         if let Some(inner) = self.inner {
             response.raw_header("Content-Type", "text/html; charset=utf-8");
 
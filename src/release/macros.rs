@@ -1,4 +1,4 @@
-/// Used in the fairing of `HandlebarsResponse` to include Handlebars files into your executable binary file. You need to specify each file's name and its path relative to the directory containing the manifest of your package. In order to reduce the compilation time and allow to hot-reload templates, files are compiled into your executable binary file together, only when you are using the **release** profile.
+/// Used in the fairing of `HandlebarsResponse` to include Handlebars files into your executable binary file. You need to specify each file's name and its path relative to the directory containing the manifest of your package. A file named like `terms.de.hbs` with the name `terms` is registered as `terms.de`, the `de` variant of `terms` for `HandlebarsContextManager::build_localized`. Only two-letter languages are detected from file names, and a name like `terms@yue` registers a variant of any language explicitly. In order to reduce the compilation time and allow to hot-reload templates, files are compiled into your executable binary file together, only when you are using the **release** profile.
 #[macro_export]
 macro_rules! handlebars_resources_initialize {
     ( $handlebars:expr, $($name:expr => $path:expr), * $(,)* ) => {
@@ -6,39 +6,155 @@ macro_rules! handlebars_resources_initialize {
             use ::std::fs;
             use ::std::collections::HashSet;
 
-            let mut set: HashSet<&str> = HashSet::new();
+            let mut set: HashSet<String> = HashSet::new();
 
             $(
-                if set.contains($name) {
-                    panic!("The name `{}` is duplicated.", $name);
+                let name = $name;
+                let name = $crate::template_variant_name(name, $crate::manifest_dir_macros::path!($path)).unwrap_or_else(|| panic!("The language tag of the name `{}` is invalid.", name));
+
+                if set.contains(name.as_ref()) {
+                    panic!("The name `{}` is duplicated.", name);
                 } else {
-                    $handlebars.register_template_string($name, include_str!($crate::manifest_dir_macros::path!($path))).unwrap();
+                    $handlebars.register_template_string(name.as_ref(), include_str!($crate::manifest_dir_macros::path!($path))).unwrap();
 
-                    set.insert($name);
+                    set.insert(name.into_owned());
                 }
             )*
         }
     };
 }
 
-/// Used for wrapping a `HandlebarsResponse` and its constructor, and use a **key** to cache its HTML and ETag in memory. The cache is generated only when you are using the **release** profile.
+/// Used in the fairing of `HandlebarsResponse` to register the `asset`, `asset_integrity` and `asset_preload` helpers with an asset manifest. You need to specify the base URL of the assets and the path of the manifest relative to the directory containing the manifest of your package. The asset manifest is compiled into your executable binary file, only when you are using the **release** profile. Otherwise, it is read again whenever it is modified.
+#[macro_export]
+macro_rules! handlebars_asset_manifest {
+    ($handlebars:expr, $base:expr, $path:expr $(,)*) => {
+        $crate::register_asset_helpers(
+            &mut *$handlebars,
+            $crate::AssetManifest::from_json(
+                $base,
+                include_str!($crate::manifest_dir_macros::path!($path)),
+            )
+            .unwrap(),
+        )
+    };
+}
+
+/// Used in the fairing of `HandlebarsResponse` to include files for the `sri` and `include_raw` helpers. You need to specify each file's path relative to the directory containing the manifest of your package, which is also its name in templates. In order to reduce the compilation time and allow to hot-reload files, files are compiled into your executable binary file together, only when you are using the **release** profile. Only available with the `helper_file` feature.
+#[cfg(feature = "helper_file")]
+#[macro_export]
+macro_rules! handlebars_files_initialize {
+    ( $handlebars:expr, $($path:expr), * $(,)* ) => {
+        {
+            let mut files = $crate::IncludedFiles::new();
+
+            $(
+                files.insert_bytes($path, include_bytes!($crate::manifest_dir_macros::path!($path)));
+            )*
+
+            $crate::register_file_helpers(&mut *$handlebars, files);
+        }
+    };
+}
+
+/// Used in the fairing of `HandlebarsResponse` to register the `t` and `locale` helpers with Fluent resources. You need to specify the default locale, and then each resource's locale and its path relative to the directory containing the manifest of your package. A locale can have multiple resources. In order to reduce the compilation time and allow to hot-reload translations, resources are compiled into your executable binary file together, only when you are using the **release** profile. Only available with the `i18n` feature.
+#[cfg(feature = "i18n")]
+#[macro_export]
+macro_rules! handlebars_translations_initialize {
+    ( $handlebars:expr, $default:expr, $($locale:expr => $path:expr), * $(,)* ) => {
+        {
+            let mut translations = $crate::Translations::new($default).unwrap();
+
+            $(
+                translations.insert_str($locale, include_str!($crate::manifest_dir_macros::path!($path))).unwrap();
+            )*
+
+            $crate::register_translation_helpers(&mut *$handlebars, translations);
+        }
+    };
+}
+
+/// Used for wrapping a `HandlebarsResponse` and its constructor, and use a **key** to cache its HTML and ETag in memory. The key can be a string or a `CacheKey`, whose **Vary** header is added to the response. `handlebars_response_cache!(localized cm, etag_if_none_match, locale, key, name, data)` builds the response by `build_localized`, which can be preceded by `enable_minify`, `disable_minify` or `auto_minify` like `handlebars_response!`, and appends the resolved variant of the template to the key. The cache is generated only when you are using the **release** profile.
 #[macro_export]
 macro_rules! handlebars_response_cache {
-    ($cm:expr, $etag_if_none_match:expr, $key:expr, $gen:block) => {
-        match $cm.build_from_cache(&$etag_if_none_match, &$key) {
+    (localized $cm:expr, $etag_if_none_match:expr, $locale:expr, $key:expr, $name:expr) => {{
+        use ::std::collections::HashMap;
+
+        let map: HashMap<u8, u8> = HashMap::new();
+
+        $crate::handlebars_response_cache!(localized $cm, $etag_if_none_match, $locale, $key, $name, map)
+    }};
+    (localized $cm:expr, $etag_if_none_match:expr, $locale:expr, $key:expr, $name:expr, $data:expr) => {
+        $crate::handlebars_response_cache!(localized enable_minify $cm, $etag_if_none_match, $locale, $key, $name, $data)
+    };
+    (localized enable_minify $cm:expr, $etag_if_none_match:expr, $locale:expr, $key:expr, $name:expr) => {{
+        use ::std::collections::HashMap;
+
+        let map: HashMap<u8, u8> = HashMap::new();
+
+        $crate::handlebars_response_cache!(localized enable_minify $cm, $etag_if_none_match, $locale, $key, $name, map)
+    }};
+    (localized enable_minify $cm:expr, $etag_if_none_match:expr, $locale:expr, $key:expr, $name:expr, $data:expr) => {{
+        let locale = &$locale;
+        let name = &$name;
+        let key = &$key;
+        let cache_key = $cm.localized_cache_key(locale, name, $crate::AsCacheKey::as_cache_key(key));
+
+        let res = match $cm.build_from_cache(&$etag_if_none_match, &cache_key) {
             Some(res) => res,
-            None => {
-                let res = $gen;
-
-                match res.into_html_and_etag() {
-                    Some((content, etag)) => {
-                        let res = $crate::HandlebarsResponse::build_cache(content.clone(), &etag);
-                        $cm.insert($key, (content, ::std::sync::Arc::new(etag)));
-                        res
-                    },
-                    None => $crate::HandlebarsResponse::not_modified(),
-                }
-            },
+            None => $cm.cache_response(
+                cache_key,
+                $cm.build_localized(&$crate::EtagIfNoneMatch::default(), true, locale, name, &$data),
+            ),
+        };
+
+        $crate::AsCacheKey::prepare_response(key, res)
+    }};
+    (localized disable_minify $cm:expr, $etag_if_none_match:expr, $locale:expr, $key:expr, $name:expr) => {{
+        use ::std::collections::HashMap;
+
+        let map: HashMap<u8, u8> = HashMap::new();
+
+        $crate::handlebars_response_cache!(localized disable_minify $cm, $etag_if_none_match, $locale, $key, $name, map)
+    }};
+    (localized disable_minify $cm:expr, $etag_if_none_match:expr, $locale:expr, $key:expr, $name:expr, $data:expr) => {{
+        let locale = &$locale;
+        let name = &$name;
+        let key = &$key;
+        let cache_key = $cm.localized_cache_key(locale, name, $crate::AsCacheKey::as_cache_key(key));
+
+        let res = match $cm.build_from_cache(&$etag_if_none_match, &cache_key) {
+            Some(res) => res,
+            None => $cm.cache_response(
+                cache_key,
+                $cm.build_localized(&$crate::EtagIfNoneMatch::default(), false, locale, name, &$data),
+            ),
+        };
+
+        $crate::AsCacheKey::prepare_response(key, res)
+    }};
+    (localized auto_minify $cm:expr, $etag_if_none_match:expr, $locale:expr, $key:expr, $name:expr) => {{
+        use ::std::collections::HashMap;
+
+        let map: HashMap<u8, u8> = HashMap::new();
+
+        $crate::handlebars_response_cache!(localized auto_minify $cm, $etag_if_none_match, $locale, $key, $name, map)
+    }};
+    (localized auto_minify $cm:expr, $etag_if_none_match:expr, $locale:expr, $key:expr, $name:expr, $data:expr) => {
+        if cfg!(debug_assertions) {
+            $crate::handlebars_response_cache!(localized disable_minify $cm, $etag_if_none_match, $locale, $key, $name, $data)
+        } else {
+            $crate::handlebars_response_cache!(localized enable_minify $cm, $etag_if_none_match, $locale, $key, $name, $data)
         }
     };
+    ($cm:expr, $etag_if_none_match:expr, $key:expr, $gen:block) => {{
+        let key = &$key;
+        let cache_key = $crate::AsCacheKey::as_cache_key(key);
+
+        let res = match $cm.build_from_cache(&$etag_if_none_match, &cache_key) {
+            Some(res) => res,
+            None => $cm.cache_response(cache_key.into_owned(), $gen),
+        };
+
+        $crate::AsCacheKey::prepare_response(key, res)
+    }};
 }
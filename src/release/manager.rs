@@ -1,32 +1,120 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    borrow::Cow,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard},
+};
 
-use handlebars::Handlebars;
-use lru_time_cache::LruCache;
+use handlebars::{Handlebars, TemplateError};
 use serde::Serialize;
 
-use super::HandlebarsResponse;
-use crate::{functions::compute_data_etag, EntityTag, EtagIfNoneMatch};
+use super::{
+    snapshot::{read_snapshot, write_snapshot},
+    HandlebarsResponse,
+};
+#[cfg(feature = "i18n")]
+use crate::Translations;
+use crate::{
+    functions::compute_data_etag, CacheEntry, EtagIfNoneMatch, Locale, ResponseCache,
+    TemplateVersions,
+};
 
-#[allow(clippy::type_complexity)]
 /// To monitor the state of Handlebars.
 #[derive(Educe)]
 #[educe(Debug)]
 pub struct HandlebarsContextManager {
-    pub handlebars: Handlebars<'static>,
+    pub(crate) handlebars:        RwLock<Handlebars<'static>>,
     #[educe(Debug(ignore))]
-    cache_table:    Mutex<LruCache<String, (Arc<str>, Arc<EntityTag<'static>>)>>,
+    pub(crate) cache_table:       Arc<dyn ResponseCache>,
+    pub(crate) template_versions: Arc<RwLock<TemplateVersions>>,
+    #[cfg(feature = "i18n")]
+    pub(crate) translations:      Option<Arc<Translations>>,
 }
 
 impl HandlebarsContextManager {
     #[inline]
     pub(crate) fn new(
         handlebars: Handlebars<'static>,
-        cache_capacity: usize,
+        cache_table: Box<dyn ResponseCache>,
     ) -> HandlebarsContextManager {
+        let template_versions = Arc::new(RwLock::new(TemplateVersions::new(&handlebars)));
+
         HandlebarsContextManager {
-            handlebars,
-            cache_table: Mutex::new(LruCache::with_capacity(cache_capacity)),
+            handlebars: RwLock::new(handlebars),
+            cache_table: Arc::from(cache_table),
+            template_versions,
+            #[cfg(feature = "i18n")]
+            translations: crate::i18n::take_registered_translations(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn template_versions(&self) -> RwLockReadGuard<'_, TemplateVersions> {
+        self.template_versions.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the registered templates and helpers. Use `register_template_file`, `register_template_string` and `unregister_template` to change the templates, so that the caches rendered from them are dropped.
+    #[inline]
+    pub fn handlebars(&self) -> RwLockReadGuard<'_, Handlebars<'static>> {
+        self.handlebars.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Change the registered templates, recompute their versions, and drop the caches rendered from templates which have been changed or unregistered, including the caches of templates using them as partials.
+    fn update_templates<T>(&self, f: impl FnOnce(&mut Handlebars<'static>) -> T) -> T {
+        let mut handlebars = self.handlebars.write().unwrap_or_else(PoisonError::into_inner);
+
+        let result = f(&mut handlebars);
+
+        let template_versions = TemplateVersions::new(&handlebars);
+
+        let mut current = self.template_versions.write().unwrap_or_else(PoisonError::into_inner);
+
+        if *current != template_versions {
+            *current = template_versions;
+
+            drop(current);
+
+            let template_versions = self.template_versions();
+
+            self.cache_table.remove_if(&|_, entry| !template_versions.is_current(&entry.templates));
         }
+
+        result
+    }
+
+    /// Register a template from a path. Caches rendered from the template it replaces, or from templates using it as a partial, are dropped.
+    #[inline]
+    pub fn register_template_file<S: Into<String>, P: Into<PathBuf>>(
+        &self,
+        name: S,
+        file_path: P,
+    ) -> Result<(), Box<TemplateError>> {
+        let name = name.into();
+        let file_path = file_path.into();
+
+        self.update_templates(|handlebars| {
+            handlebars.register_template_file(&name, file_path).map_err(Box::new)
+        })
+    }
+
+    /// Register a template from a string. Caches rendered from the template it replaces, or from templates using it as a partial, are dropped.
+    #[inline]
+    pub fn register_template_string<S: Into<String>, T: AsRef<str>>(
+        &self,
+        name: S,
+        tpl_str: T,
+    ) -> Result<(), Box<TemplateError>> {
+        let name = name.into();
+
+        self.update_templates(|handlebars| {
+            handlebars.register_template_string(&name, tpl_str).map_err(Box::new)
+        })
+    }
+
+    /// Unregister a template. Caches rendered from the template, or from templates using it as a partial, are dropped.
+    #[inline]
+    pub fn unregister_template<S: AsRef<str>>(&self, name: S) {
+        self.update_templates(|handlebars| handlebars.unregister_template(name.as_ref()))
     }
 
     /// Build a `HandlebarsResponse`.
@@ -38,8 +126,10 @@ impl HandlebarsContextManager {
         name: S,
         context: V,
     ) -> HandlebarsResponse {
-        self.handlebars
-            .render(name.as_ref(), &context)
+        let name = name.as_ref();
+
+        self.handlebars()
+            .render(name, &context)
             .map(|html| {
                 let etag = compute_data_etag(html.as_bytes());
 
@@ -49,62 +139,136 @@ impl HandlebarsContextManager {
                     let html = if minify { html_minifier::minify(html).unwrap() } else { html };
 
                     HandlebarsResponse::build_not_cache(html, &etag)
+                        .with_templates(self.template_versions().get(name))
                 }
             })
             .unwrap()
     }
 
-    /// Build a `HandlebarsResponse`.
+    /// Resolve the variant of a template for a locale. For `de-AT`, `terms` is resolved to `terms.de-AT`, then `terms.de`, and then `terms` itself, whichever is registered first.
+    #[inline]
+    pub fn resolve_template<'a>(&self, locale: &Locale, name: &'a str) -> Cow<'a, str> {
+        let handlebars = self.handlebars();
+
+        for tag in locale.fallbacks() {
+            let variant = format!("{}.{}", name, tag);
+
+            if handlebars.has_template(&variant) {
+                return Cow::Owned(variant);
+            }
+        }
+
+        Cow::Borrowed(name)
+    }
+
+    /// Build a `HandlebarsResponse` in a locale, which is used by the `t` and `locale` helpers of the `i18n` feature, from the variant of the template resolved by `resolve_template`. To cache the response, use `handlebars_response_cache!(localized ...)`, which keys the cache by `localized_cache_key`.
     #[inline]
-    pub fn build_from_cache<K: AsRef<str>>(
+    pub fn build_localized<S: AsRef<str>, V: Serialize>(
         &self,
         etag_if_none_match: &EtagIfNoneMatch<'_>,
+        minify: bool,
+        locale: &Locale,
+        name: S,
+        context: V,
+    ) -> HandlebarsResponse {
+        let name = self.resolve_template(locale, name.as_ref());
+
+        locale.scope(|| self.build(etag_if_none_match, minify, name, context))
+    }
+
+    /// Get the key of the cache of a template built by `build_localized`, which is `key` followed by `@`, the language tag of the variant of the template resolved by `resolve_template`, and, with the `i18n` feature, the locale negotiated by the translations registered by `handlebars_translations_initialize!`, like `terms@de-AT`. If there is no variant and no negotiated locale, the key is kept as it is.
+    #[inline]
+    pub fn localized_cache_key<S: AsRef<str>, K: Into<String>>(
+        &self,
+        locale: &Locale,
+        name: S,
         key: K,
-    ) -> Option<HandlebarsResponse> {
-        self.cache_table.lock().unwrap().get(key.as_ref()).map(|(html, etag)| {
-            if etag_if_none_match.weak_eq(etag) {
-                HandlebarsResponse::not_modified()
-            } else {
-                HandlebarsResponse::build_cache(html.clone(), etag)
-            }
-        })
+    ) -> String {
+        let name = self.resolve_template(locale, name.as_ref());
+
+        let mut key = key.into();
+
+        #[cfg(feature = "i18n")]
+        let suffix = locale.cache_key_suffix(&name, self.translations.as_deref());
+        #[cfg(not(feature = "i18n"))]
+        let suffix = locale.cache_key_suffix(&name);
+
+        if let Some(suffix) = suffix {
+            key.push('@');
+            key.push_str(&suffix);
+        }
+
+        key
     }
 
     /// Render a template.
     #[inline]
     pub fn render<S: AsRef<str>, V: Serialize>(&self, name: S, context: V) -> String {
-        self.handlebars.render(name.as_ref(), &context).unwrap()
+        self.handlebars().render(name.as_ref(), &context).unwrap()
     }
 
-    /// Clear cache.
+    /// Render the variant of a template in a locale.
     #[inline]
-    pub fn clear_cache(&self) {
-        self.cache_table.lock().unwrap().clear();
+    pub fn render_localized<S: AsRef<str>, V: Serialize>(
+        &self,
+        locale: &Locale,
+        name: S,
+        context: V,
+    ) -> String {
+        let name = self.resolve_template(locale, name.as_ref());
+
+        locale.scope(|| self.render(name, context))
     }
 
-    /// Check if a cache key exists.
+    /// Compute a fingerprint of all registered templates, which changes whenever a template is added, removed or modified.
     #[inline]
-    pub fn contains_key<S: AsRef<str>>(&self, key: S) -> bool {
-        self.cache_table.lock().unwrap().get(key.as_ref()).is_some()
+    pub fn template_fingerprint(&self) -> String {
+        self.template_versions().fingerprint()
     }
 
-    /// Get the cache by a specific key.
+    /// Save all caches which have not expired to a file, along with their expiry times. Return the number of saved caches.
     #[inline]
-    pub fn get<S: AsRef<str>>(&self, key: S) -> Option<(Arc<str>, Arc<EntityTag<'static>>)> {
-        self.cache_table
-            .lock()
-            .unwrap()
-            .get(key.as_ref())
-            .map(|(html, etag)| (html.clone(), etag.clone()))
+    pub fn save_cache_snapshot<P: AsRef<Path>>(&self, path: P) -> io::Result<usize> {
+        let mut entries: Vec<(String, CacheEntry)> = self
+            .cache_table
+            .entries()
+            .into_iter()
+            .filter_map(|info| self.cache_table.peek(&info.key).map(|entry| (info.key, entry)))
+            .filter(|(_, entry)| !entry.is_expired())
+            .collect();
+
+        // from the least recently used one, so that the recency is kept after restoring
+        entries.reverse();
+
+        write_snapshot(path, &self.template_fingerprint(), &entries)?;
+
+        Ok(entries.len())
     }
 
-    /// Insert a cache.
+    /// Restore caches from a file which is saved by `save_cache_snapshot`. If the file does not exist, or the templates have been changed since the snapshot was saved, nothing is restored. Caches which have expired since are not restored either. Return the number of restored caches.
     #[inline]
-    pub fn insert<S: Into<String>>(
-        &self,
-        key: S,
-        cache: (Arc<str>, Arc<EntityTag<'static>>),
-    ) -> Option<(Arc<str>, Arc<EntityTag<'static>>)> {
-        self.cache_table.lock().unwrap().insert(key.into(), cache)
+    pub fn load_cache_snapshot<P: AsRef<Path>>(&self, path: P) -> io::Result<usize> {
+        let (fingerprint, entries) = match read_snapshot(path)? {
+            Some(snapshot) => snapshot,
+            None => return Ok(0),
+        };
+
+        if fingerprint != self.template_fingerprint() {
+            return Ok(0);
+        }
+
+        let mut count = 0;
+
+        for (key, entry) in entries {
+            if !entry.is_expired() {
+                self.cache_table.insert(key, entry);
+
+                count += 1;
+            }
+        }
+
+        self.cache_table.reset_stats();
+
+        Ok(count)
     }
 }
@@ -2,6 +2,8 @@ mod handlebars_response;
 
 mod manager;
 
+mod snapshot;
+
 mod fairing;
 
 mod macros;
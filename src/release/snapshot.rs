@@ -0,0 +1,91 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crate::{decode_entry, encode_entry, CacheEntry};
+
+const MAGIC: &[u8] = b"RIHCACHE3\n";
+
+// The layout of a snapshot file is
+// [MAGIC][length of the fingerprint (u32)][fingerprint]([length of a key (u32)][key][length of an entry (u32)][entry])*,
+// and the integers are big-endian. Entries are encoded by `encode_entry`.
+
+#[inline]
+fn write_bytes(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    buffer.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(bytes);
+}
+
+#[inline]
+fn read_bytes<'a>(data: &mut &'a [u8]) -> io::Result<&'a [u8]> {
+    let invalid = || io::Error::new(ErrorKind::InvalidData, "the cache snapshot is truncated");
+
+    let len = data.get(..4).ok_or_else(invalid)?;
+    let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+
+    let bytes = data.get(4..(4 + len)).ok_or_else(invalid)?;
+
+    *data = &data[(4 + len)..];
+
+    Ok(bytes)
+}
+
+/// Write caches to a file. The file is replaced atomically.
+pub(crate) fn write_snapshot<P: AsRef<Path>>(
+    path: P,
+    fingerprint: &str,
+    entries: &[(String, CacheEntry)],
+) -> io::Result<()> {
+    let path = path.as_ref();
+
+    let mut buffer = Vec::from(MAGIC);
+
+    write_bytes(&mut buffer, fingerprint.as_bytes());
+
+    for (key, entry) in entries {
+        write_bytes(&mut buffer, key.as_bytes());
+        write_bytes(&mut buffer, &encode_entry(entry));
+    }
+
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+
+    fs::write(&temp_path, buffer)?;
+    fs::rename(&temp_path, path)
+}
+
+/// Read caches from a file. Return `None` if the file does not exist.
+#[allow(clippy::type_complexity)]
+pub(crate) fn read_snapshot<P: AsRef<Path>>(
+    path: P,
+) -> io::Result<Option<(String, Vec<(String, CacheEntry)>)>> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message);
+
+    let mut data =
+        data.strip_prefix(MAGIC).ok_or_else(|| invalid("the file is not a cache snapshot"))?;
+
+    let fingerprint = String::from_utf8(read_bytes(&mut data)?.to_vec())
+        .map_err(|_| invalid("the fingerprint is not UTF-8"))?;
+
+    let mut entries = Vec::new();
+
+    while !data.is_empty() {
+        let key = String::from_utf8(read_bytes(&mut data)?.to_vec())
+            .map_err(|_| invalid("a key is not UTF-8"))?;
+
+        let entry =
+            decode_entry(read_bytes(&mut data)?).ok_or_else(|| invalid("an entry is broken"))?;
+
+        entries.push((key, entry));
+    }
+
+    Ok(Some((fingerprint, entries)))
+}
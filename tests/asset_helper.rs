@@ -0,0 +1,87 @@
+use std::{fs, thread, time::Duration};
+
+use handlebars::Handlebars;
+use rocket::local::blocking::Client;
+use rocket_include_handlebars::*;
+use serde_json::json;
+
+const MANIFEST: &str = r#"{
+    "app.js": "app.3f9a1c.js",
+    "src/main.ts": {"file": "assets/main.4889e940.js", "css": ["assets/main.b82dbe22.css"], "integrity": "sha384-abc"},
+    "fonts/inter.woff2": "fonts/inter.1a2b3c.woff2"
+}"#;
+
+#[test]
+fn manifest_formats() {
+    let mut handlebars = Handlebars::new();
+
+    register_asset_helpers(
+        &mut handlebars,
+        AssetManifest::from_json("/static/", MANIFEST).unwrap(),
+    );
+
+    let render = |template: &str| handlebars.render_template(template, &json!({})).unwrap();
+
+    assert_eq!("/static/app.3f9a1c.js", render("{{asset \"app.js\"}}"));
+    assert_eq!("/static/assets/main.4889e940.js", render("{{asset \"src/main.ts\"}}"));
+    assert_eq!("sha384-abc", render("{{asset_integrity \"src/main.ts\"}}"));
+    assert_eq!("", render("{{asset_integrity \"app.js\"}}"));
+    assert_eq!(
+        "<link rel=\"preload\" href=\"/static/assets/main.4889e940.js\" as=\"script\" \
+         integrity=\"sha384-abc\" crossorigin=\"anonymous\">",
+        render("{{asset_preload \"src/main.ts\"}}")
+    );
+    assert_eq!(
+        "<link rel=\"preload\" href=\"/static/fonts/inter.1a2b3c.woff2\" as=\"font\" \
+         crossorigin=\"anonymous\">",
+        render("{{asset_preload \"fonts/inter.woff2\"}}")
+    );
+    assert_eq!(
+        "<link rel=\"preload\" href=\"/static/app.3f9a1c.js\" as=\"fetch\">",
+        render("{{asset_preload \"app.js\" as=\"fetch\"}}")
+    );
+
+    assert!(handlebars.render_template("{{asset \"missing.js\"}}", &json!({})).is_err());
+    assert!(handlebars.render_template("{{asset}}", &json!({})).is_err());
+
+    assert!(AssetManifest::from_json("", "[]").is_err());
+    assert!(AssetManifest::from_json("", r#"{"a.js": {"css": []}}"#).is_err());
+}
+
+#[test]
+fn reload_manifest() {
+    let path = std::env::temp_dir().join("rocket-include-handlebars-asset-manifest-test.json");
+
+    fs::write(&path, r#"{"app.js": "app.1.js"}"#).unwrap();
+
+    let manifest = AssetManifest::from_file("", &path).unwrap();
+
+    assert_eq!("/app.1.js", manifest.url(&manifest.get("app.js").unwrap()));
+
+    // make sure the modification time changes on file systems with a coarse resolution
+    thread::sleep(Duration::from_millis(1100));
+
+    fs::write(&path, r#"{"app.js": "app.2.js"}"#).unwrap();
+
+    manifest.reload_if_needed().unwrap();
+
+    assert_eq!("/app.2.js", manifest.url(&manifest.get("app.js").unwrap()));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn fairing() {
+    let rocket = rocket::build().attach(HandlebarsResponse::fairing(|handlebars| {
+        handlebars_asset_manifest!(handlebars, "/static", "tests/assets/manifest.json");
+
+        handlebars
+            .register_template_string("page", "<script src=\"{{asset \"app.js\"}}\"></script>")
+            .unwrap();
+    }));
+
+    let client = Client::untracked(rocket).unwrap();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    assert_eq!("<script src=\"/static/app.3f9a1c.js\"></script>", cm.render("page", json!({})));
+}
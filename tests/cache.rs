@@ -0,0 +1,345 @@
+#![cfg(not(debug_assertions))]
+
+use std::sync::Arc;
+
+use rocket::local::blocking::Client;
+use rocket_include_handlebars::*;
+use serde_json::json;
+
+fn client() -> Client {
+    let rocket = rocket::build().attach(handlebars_resources_initializer!(
+        "index" => "examples/views/index.hbs",
+        "index2" => "examples/views/index2.hbs"
+    ));
+
+    Client::untracked(rocket).unwrap()
+}
+
+fn cache(html: &str) -> (Arc<str>, Arc<EntityTag<'static>>) {
+    (Arc::from(html), Arc::new(EntityTag::from_data(html)))
+}
+
+#[test]
+fn remove_and_entries() {
+    let client = client();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    cm.insert("blog/1", cache("<p>1</p>"));
+    cm.insert("blog/2", cache("<p>22</p>"));
+    cm.insert("home", cache("<p>home</p>"));
+
+    let entries = cm.entries();
+
+    assert_eq!(
+        vec!["home", "blog/2", "blog/1"],
+        entries.iter().map(|e| e.key.as_str()).collect::<Vec<_>>()
+    );
+    assert_eq!(9, entries[1].size);
+
+    assert!(cm.remove("home").is_some());
+    assert!(cm.remove("home").is_none());
+
+    assert_eq!(2, cm.remove_prefix("blog/"));
+    assert!(cm.entries().is_empty());
+}
+
+#[test]
+fn cache_warming() {
+    let rocket = rocket::build()
+        .attach(handlebars_resources_initializer!(
+            "index" => "examples/views/index.hbs"
+        ))
+        .attach(HandlebarsResponse::cache_warming_fairing(|warmer| {
+            warmer
+                .warm("home", |cm| {
+                    Ok(handlebars_response!(cm, EtagIfNoneMatch::default(), "index"))
+                })
+                .warm("missing", |cm| {
+                    Ok(handlebars_response!(cm, EtagIfNoneMatch::default(), "missing"))
+                })
+                .warm("error", |_| Err("the database is not ready".into()))
+                .warm("not_modified", |_| Ok(HandlebarsResponse::not_modified()));
+        }));
+
+    let client = Client::untracked(rocket).unwrap();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    assert!(cm.contains_key("home"));
+    assert!(!cm.contains_key("missing"));
+    assert!(!cm.contains_key("error"));
+    assert!(!cm.contains_key("not_modified"));
+}
+
+#[test]
+fn stats() {
+    let client = client();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    cm.track_stats_prefix("blog/");
+
+    assert!(cm.build_from_cache(&EtagIfNoneMatch::default(), "blog/1").is_none());
+
+    cm.insert("blog/1", cache("<p>1</p>"));
+    cm.insert("home", cache("<p>home</p>"));
+
+    assert!(cm.build_from_cache(&EtagIfNoneMatch::default(), "blog/1").is_some());
+    assert!(cm.build_from_cache(&EtagIfNoneMatch::default(), "home").is_some());
+
+    let stats = cm.stats();
+
+    assert_eq!(2, stats.hits);
+    assert_eq!(1, stats.misses);
+    assert_eq!(2, stats.insertions);
+    assert_eq!(0, stats.evictions);
+    assert_eq!(2, stats.entries);
+    assert_eq!(19, stats.bytes);
+
+    let stats = cm.stats_prefix("blog/").unwrap();
+
+    assert_eq!(1, stats.hits);
+    assert_eq!(1, stats.misses);
+    assert_eq!(1, stats.entries);
+    assert_eq!(8, stats.bytes);
+
+    assert!(cm.stats_prefix("docs/").is_none());
+}
+
+#[test]
+fn snapshot() {
+    let path = std::env::temp_dir().join("rocket-include-handlebars-snapshot-test");
+
+    {
+        let client = client();
+        let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+        cm.insert("blog/1", cache("<p>1</p>"));
+        cm.insert("home", cache("<p>home</p>"));
+
+        assert_eq!(2, cm.save_cache_snapshot(&path).unwrap());
+    }
+
+    {
+        let client = client();
+        let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+        assert_eq!(2, cm.load_cache_snapshot(&path).unwrap());
+        assert_eq!(
+            vec!["home", "blog/1"],
+            cm.entries().iter().map(|e| e.key.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!("<p>home</p>", cm.get("home").unwrap().0.as_ref());
+    }
+
+    {
+        let rocket = rocket::build().attach(handlebars_resources_initializer!(
+            "index" => "examples/views/index.hbs"
+        ));
+        let client = Client::untracked(rocket).unwrap();
+        let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+        assert_eq!(0, cm.load_cache_snapshot(&path).unwrap());
+        assert!(cm.entries().is_empty());
+    }
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn snapshot_expiry() {
+    let path = std::env::temp_dir().join("rocket-include-handlebars-snapshot-expiry-test");
+
+    let client = || {
+        let rocket = rocket::build().attach(HandlebarsResponse::fairing(|handlebars| {
+            handlebars
+                .register_template_string(
+                    "page",
+                    "{{#cache \"soon\" ttl=1}}a{{/cache}}{{#cache \"later\" ttl=300}}b{{/cache}}",
+                )
+                .unwrap();
+        }));
+
+        Client::untracked(rocket).unwrap()
+    };
+
+    {
+        let client = client();
+        let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+        assert_eq!("ab", cm.render("page", json!({})));
+        assert_eq!(2, cm.save_cache_snapshot(&path).unwrap());
+    }
+
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+
+    {
+        let client = client();
+        let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+        assert_eq!(1, cm.load_cache_snapshot(&path).unwrap());
+        assert!(!cm.contains_key("fragment:soon"));
+        assert!(cm.contains_key("fragment:later"));
+    }
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn template_versions() {
+    let store = Arc::new(MemoryKeyValueStore::new());
+
+    let client_with = |source: &'static str| {
+        let store = store.clone();
+
+        let rocket =
+            rocket::build().attach(HandlebarsResponse::fairing_custom_cache(move |handlebars| {
+                handlebars
+                    .register_template_string("layout", "<main>{{> @partial-block}}</main>")
+                    .unwrap();
+                handlebars.register_template_string("index", source).unwrap();
+
+                KeyValueResponseCache::new(store.clone())
+            }));
+
+        Client::untracked(rocket).unwrap()
+    };
+
+    let render = |cm: &HandlebarsContextManager| {
+        handlebars_response_cache!(cm, EtagIfNoneMatch::default(), "home", {
+            handlebars_response!(
+                cm,
+                EtagIfNoneMatch::default(),
+                "index",
+                json!({ "title": "Home" })
+            )
+        })
+    };
+
+    {
+        let client = client_with("{{#> layout}}<p>{{title}}</p>{{/layout}}");
+        let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+        render(cm);
+
+        let entry = KeyValueResponseCache::new(store.clone()).peek("home").unwrap();
+
+        assert_eq!("<main><p>Home</p></main>", entry.html.as_ref());
+        assert_eq!(
+            vec!["index", "layout"],
+            entry.templates.iter().map(|t| t.name.as_str()).collect::<Vec<_>>()
+        );
+    }
+
+    {
+        let client = client_with("{{#> layout}}<p>{{title}}</p>{{/layout}}");
+        let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+        assert!(cm.build_from_cache(&EtagIfNoneMatch::default(), "home").is_some());
+        assert_eq!(1, cm.remove_template_caches("layout"));
+        assert!(!cm.contains_key("home"));
+
+        render(cm);
+    }
+
+    {
+        let client = client_with("{{#> layout}}<h1>{{title}}</h1>{{/layout}}");
+        let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+        assert!(cm.build_from_cache(&EtagIfNoneMatch::default(), "home").is_none());
+        assert!(!cm.contains_key("home"));
+    }
+}
+
+#[test]
+fn template_registration() {
+    let rocket = rocket::build().attach(HandlebarsResponse::fairing(|handlebars| {
+        handlebars.register_template_string("layout", "<main>{{> @partial-block}}</main>").unwrap();
+        handlebars
+            .register_template_string("index", "{{#> layout}}<p>index</p>{{/layout}}")
+            .unwrap();
+        handlebars.register_template_string("about", "<p>about</p>").unwrap();
+    }));
+
+    let client = Client::untracked(rocket).unwrap();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    let render = |key: &str, name: &str| {
+        handlebars_response_cache!(cm, EtagIfNoneMatch::default(), key, {
+            handlebars_response!(cm, EtagIfNoneMatch::default(), name)
+        })
+    };
+
+    render("index", "index");
+    render("about", "about");
+    cm.insert("raw", cache("<p>raw</p>"));
+
+    let fingerprint = cm.template_fingerprint();
+
+    cm.register_template_string("layout", "<div>{{> @partial-block}}</div>").unwrap();
+
+    assert_ne!(fingerprint, cm.template_fingerprint());
+    assert!(!cm.contains_key("index"));
+    assert!(cm.contains_key("about"));
+    assert!(cm.contains_key("raw"));
+
+    render("index", "index");
+
+    assert_eq!("<div><p>index</p></div>", cm.get("index").unwrap().0.as_ref());
+
+    // registering the same template again keeps its caches
+    cm.register_template_string("about", "<p>about</p>").unwrap();
+
+    assert!(cm.contains_key("about"));
+
+    assert!(cm.register_template_string("about", "{{#if}}").is_err());
+    assert!(cm.contains_key("about"));
+
+    cm.unregister_template("about");
+
+    assert!(!cm.contains_key("about"));
+    assert!(cm.contains_key("index"));
+    assert!(cm.contains_key("raw"));
+}
+
+#[test]
+fn fragment_cache() {
+    let rocket = rocket::build().attach(HandlebarsResponse::fairing(|handlebars| {
+        handlebars
+            .register_template_string(
+                "page",
+                "{{#cache \"sidebar\"}}<nav>{{count}}</nav>{{/cache}}<p>{{title}}</p>",
+            )
+            .unwrap();
+        handlebars
+            .register_template_string("menu", "{{#cache \"menu\" user ttl=0}}{{count}}{{/cache}}")
+            .unwrap();
+        handlebars
+            .register_template_string(
+                "pair",
+                "{{#cache \"pair\" a b ttl=\"300\"}}{{count}}{{/cache}}",
+            )
+            .unwrap();
+        handlebars
+            .register_template_string("invalid", "{{#cache \"invalid\" ttl=1.5}}{{/cache}}")
+            .unwrap();
+    }));
+
+    let client = Client::untracked(rocket).unwrap();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    assert_eq!("<nav>1</nav><p>a</p>", cm.render("page", json!({ "title": "a", "count": 1 })));
+    assert_eq!("<nav>1</nav><p>b</p>", cm.render("page", json!({ "title": "b", "count": 2 })));
+    assert!(cm.contains_key("fragment:sidebar"));
+
+    assert_eq!("1", cm.render("menu", json!({ "user": "alice", "count": 1 })));
+    assert_eq!("2", cm.render("menu", json!({ "user": "alice", "count": 2 })));
+    assert!(cm.contains_key("fragment:menu:alice"));
+    assert!(!cm.contains_key("fragment:menu:bob"));
+
+    // the parameters are escaped, so `a:b` and `c` do not share a cache with `a` and `b:c`
+    assert_eq!("1", cm.render("pair", json!({ "a": "a:b", "b": "c", "count": 1 })));
+    assert_eq!("2", cm.render("pair", json!({ "a": "a", "b": "b:c", "count": 2 })));
+    assert!(cm.contains_key("fragment:pair:a\\:b:c"));
+    assert!(cm.contains_key("fragment:pair:a:b\\:c"));
+
+    assert!(cm.handlebars().render("invalid", &json!({})).is_err());
+}
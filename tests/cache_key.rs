@@ -0,0 +1,81 @@
+use rocket::{get, http::Header, local::blocking::Client, routes, State};
+use rocket_include_handlebars::*;
+
+#[get("/list")]
+fn list(
+    cm: &State<HandlebarsContextManager>,
+    etag_if_none_match: EtagIfNoneMatch,
+    key: CacheKey<'_>,
+) -> HandlebarsResponse {
+    let key = key.query("page").header("Accept-Language").cookie("theme");
+
+    handlebars_response_cache!(cm, etag_if_none_match, key, {
+        handlebars_response!(cm, EtagIfNoneMatch::default(), "index")
+    })
+}
+
+#[get("/key")]
+fn key(key: CacheKey<'_>) -> String {
+    key.cookie("theme").header("Accept-Language").query("sort").query("page").to_key()
+}
+
+fn client() -> Client {
+    let rocket = rocket::build()
+        .attach(handlebars_resources_initializer!(
+            "index" => "examples/views/index.hbs"
+        ))
+        .mount("/", routes![list, key]);
+
+    Client::untracked(rocket).unwrap()
+}
+
+#[test]
+fn to_key() {
+    let client = client();
+
+    let key = client
+        .get("/key?page=2&sort=name")
+        .header(Header::new("Accept-Language", "de"))
+        .cookie(("theme", "dark"))
+        .dispatch()
+        .into_string()
+        .unwrap();
+
+    assert_eq!("/key\nq:page=2\nq:sort=name\nh:accept-language=de\nc:theme=dark", key);
+
+    let same_key = client
+        .get("/key?sort=%6Eame&utm=x&page=2")
+        .cookie(("theme", "dark"))
+        .header(Header::new("Accept-Language", "de"))
+        .dispatch()
+        .into_string()
+        .unwrap();
+
+    assert_eq!(key, same_key);
+
+    let key = client.get("/key?sort=a+b&x=1").dispatch().into_string().unwrap();
+
+    assert_eq!("/key\nq:sort=a%20b", key);
+    assert_eq!(key, client.get("/key?sort=a%20b").dispatch().into_string().unwrap());
+}
+
+#[test]
+fn vary() {
+    let client = client();
+
+    let response = client.get("/list?page=2").dispatch();
+
+    assert_eq!(Some("Accept-Language, Cookie"), response.headers().get_one("Vary"));
+
+    let etag = response.headers().get_one("Etag").unwrap().to_string();
+
+    // a response served from the cache, or a 304 response, has the header as well
+    let response = client.get("/list?page=2").dispatch();
+
+    assert_eq!(Some("Accept-Language, Cookie"), response.headers().get_one("Vary"));
+
+    let response = client.get("/list?page=2").header(Header::new("If-None-Match", etag)).dispatch();
+
+    assert_eq!(rocket::http::Status::NotModified, response.status());
+    assert_eq!(Some("Accept-Language, Cookie"), response.headers().get_one("Vary"));
+}
@@ -0,0 +1,92 @@
+#![cfg(all(debug_assertions, feature = "debug_cache"))]
+
+use std::{fs, sync::Arc, thread, time::Duration};
+
+use rocket::local::blocking::Client;
+use rocket_include_handlebars::*;
+
+#[test]
+fn invalidate_on_reload() {
+    let path = std::env::temp_dir().join("rocket-include-handlebars-debug-cache-test.hbs");
+
+    fs::write(&path, "<p>{{title}}</p>").unwrap();
+
+    let template_path = path.clone();
+
+    let rocket = rocket::build().attach(HandlebarsResponse::fairing(move |handlebars| {
+        handlebars.register_template_file("temp", template_path.clone()).unwrap();
+    }));
+
+    let client = Client::untracked(rocket).unwrap();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    cm.insert("temp", (Arc::from("<p></p>"), Arc::new(EntityTag::from_data("<p></p>"))));
+
+    client.get("/").dispatch();
+
+    assert!(cm.contains_key("temp"));
+
+    // make sure the modification time changes on file systems with a coarse resolution
+    thread::sleep(Duration::from_millis(1100));
+
+    fs::write(&path, "<h1>{{title}}</h1>").unwrap();
+
+    client.get("/").dispatch();
+
+    assert!(!cm.contains_key("temp"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn invalidate_changed_templates() {
+    let dir = std::env::temp_dir().join("rocket-include-handlebars-debug-cache-templates-test");
+
+    fs::create_dir_all(&dir).unwrap();
+
+    let page_path = dir.join("page.hbs");
+    let part_path = dir.join("part.hbs");
+    let other_path = dir.join("other.hbs");
+
+    fs::write(&page_path, "<main>{{> part}}</main>").unwrap();
+    fs::write(&part_path, "<p>part</p>").unwrap();
+    fs::write(&other_path, "<p>other</p>").unwrap();
+
+    let paths = (page_path, part_path.clone(), other_path);
+
+    let rocket = rocket::build().attach(HandlebarsResponse::fairing(move |handlebars| {
+        handlebars.register_template_file("page", paths.0.clone()).unwrap();
+        handlebars.register_template_file("part", paths.1.clone()).unwrap();
+        handlebars.register_template_file("other", paths.2.clone()).unwrap();
+    }));
+
+    let client = Client::untracked(rocket).unwrap();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    for name in ["page", "other"] {
+        handlebars_response_cache!(cm, EtagIfNoneMatch::default(), name, {
+            handlebars_response!(cm, EtagIfNoneMatch::default(), name)
+        });
+    }
+
+    client.get("/").dispatch();
+
+    assert!(cm.contains_key("page"));
+    assert!(cm.contains_key("other"));
+
+    // make sure the modification time changes on file systems with a coarse resolution
+    thread::sleep(Duration::from_millis(1100));
+
+    fs::write(&part_path, "<p>changed</p>").unwrap();
+
+    client.get("/").dispatch();
+
+    assert!(!cm.contains_key("page"));
+    assert!(cm.contains_key("other"));
+
+    cm.unregister_template("other");
+
+    assert!(!cm.contains_key("other"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
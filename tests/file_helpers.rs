@@ -0,0 +1,89 @@
+#![cfg(feature = "helper_file")]
+
+use std::{fs, thread, time::Duration};
+
+use handlebars::Handlebars;
+use rocket::local::blocking::Client;
+use rocket_include_handlebars::*;
+use serde_json::json;
+
+const APP_JS_SHA384: &str =
+    "sha384-M5mGpKxRozBpvsX+PXs0ssm1NdoBYDPN4gQsyCwq+RTqmuLt5T6LWQKklQd4sArc";
+
+#[test]
+fn sri_and_include_raw() {
+    let mut files = IncludedFiles::new();
+
+    files.insert_bytes("app.js", b"console.log(\"hello\");\n");
+    files.insert_bytes("logo.svg", b"<svg><circle r=\"1\"/></svg>");
+    files.insert_bytes("binary", &[0xFF, 0xFE]);
+
+    let mut handlebars = Handlebars::new();
+
+    register_file_helpers(&mut handlebars, files);
+
+    let render = |template: &str| handlebars.render_template(template, &json!({})).unwrap();
+
+    assert_eq!(APP_JS_SHA384, render("{{sri \"app.js\"}}"));
+    assert_eq!(
+        "sha256-+URFENx0A+QQSd6xM/aJKqamPAVZGytZ5O5bI017vZk=",
+        render("{{{sri \"app.js\" algorithm=\"sha256\"}}}")
+    );
+    assert_eq!("<svg><circle r=\"1\"/></svg>", render("{{include_raw \"logo.svg\"}}"));
+
+    assert!(handlebars
+        .render_template("{{sri \"app.js\" algorithm=\"md5\"}}", &json!({}))
+        .is_err());
+    assert!(handlebars.render_template("{{sri \"missing.js\"}}", &json!({})).is_err());
+    assert!(handlebars.render_template("{{include_raw \"binary\"}}", &json!({})).is_err());
+}
+
+#[test]
+fn reload_files() {
+    let path = std::env::temp_dir().join("rocket-include-handlebars-include-raw-test.svg");
+
+    fs::write(&path, "<svg>1</svg>").unwrap();
+
+    let mut files = IncludedFiles::new();
+
+    files.insert_file("icon", &path).unwrap();
+
+    assert_eq!(b"<svg>1</svg>", files.get("icon").unwrap().unwrap().as_ref());
+
+    // make sure the modification time changes on file systems with a coarse resolution
+    thread::sleep(Duration::from_millis(1100));
+
+    fs::write(&path, "<svg>2</svg>").unwrap();
+
+    assert_eq!(b"<svg>2</svg>", files.get("icon").unwrap().unwrap().as_ref());
+    assert!(files.get("missing").unwrap().is_none());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn fairing() {
+    let rocket = rocket::build().attach(HandlebarsResponse::fairing(|handlebars| {
+        handlebars_files_initialize!(handlebars, "tests/assets/app.js", "tests/assets/logo.svg");
+
+        handlebars
+            .register_template_string(
+                "page",
+                "<script src=\"/app.js\" integrity=\"{{sri \
+                 \"tests/assets/app.js\"}}\"></script>{{include_raw \"tests/assets/logo.svg\"}}",
+            )
+            .unwrap();
+    }));
+
+    let client = Client::untracked(rocket).unwrap();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    assert_eq!(
+        format!(
+            "<script src=\"/app.js\" integrity=\"{}\"></script><svg \
+             xmlns=\"http://www.w3.org/2000/svg\"><circle r=\"1\"/></svg>\n",
+            APP_JS_SHA384
+        ),
+        cm.render("page", json!({}))
+    );
+}
@@ -0,0 +1,826 @@
+use handlebars::Handlebars;
+use rocket_include_handlebars::*;
+use serde_json::{json, Value};
+
+fn handlebars(set: HelperSet) -> Handlebars<'static> {
+    let mut handlebars = Handlebars::new();
+
+    register_helpers(&mut handlebars, set);
+
+    handlebars
+}
+
+fn render(handlebars: &Handlebars, template: &str, data: &Value) -> String {
+    handlebars.render_template(template, data).unwrap()
+}
+
+#[cfg(feature = "helper_date")]
+fn date_handlebars() -> Handlebars<'static> {
+    let mut handlebars = handlebars(HelperSet::NONE);
+
+    // the current time is fixed to 2024-05-01T12:00:00Z
+    register_date_helpers(&mut handlebars, || {
+        chrono::DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().into()
+    });
+
+    handlebars
+}
+
+#[test]
+fn helper_set() {
+    let set = HelperSet::INC | HelperSet::COMPARE;
+
+    assert!(set.contains(HelperSet::INC));
+    assert!(set.contains(HelperSet::COMPARE));
+    assert!(!set.contains(HelperSet::DEC));
+    assert!(HelperSet::ALL.contains(set));
+    assert!(set.contains(HelperSet::NONE));
+
+    let handlebars = handlebars(HelperSet::INC);
+
+    assert_eq!("2", render(&handlebars, "{{inc 1}}", &json!({})));
+    assert!(handlebars.render_template("{{dec 1}}", &json!({})).is_err());
+}
+
+#[test]
+fn str_helpers() {
+    let handlebars = handlebars(HelperSet::EQ_STR | HelperSet::NE_STR);
+
+    assert_eq!("true", render(&handlebars, "{{eq_str \"a\" \"a\"}}", &json!({})));
+    assert_eq!("true", render(&handlebars, "{{ne_str \"a\" \"b\"}}", &json!({})));
+}
+
+#[test]
+fn eq_and_ne() {
+    let handlebars = handlebars(HelperSet::COMPARE);
+
+    let data = json!({
+        "int": 1,
+        "float": 1.0,
+        "string": "1",
+        "array": [1, "a", {"b": 2}],
+        "object": {"a": [1, 2], "b": null},
+    });
+
+    assert_eq!("true", render(&handlebars, "{{eq int float}}", &data));
+    assert_eq!("false", render(&handlebars, "{{eq int string}}", &data));
+    assert_eq!("true", render(&handlebars, "{{ne int string}}", &data));
+    assert_eq!("true", render(&handlebars, "{{eq array array}}", &data));
+    assert_eq!("false", render(&handlebars, "{{eq array object}}", &data));
+    assert_eq!("true", render(&handlebars, "{{eq object object}}", &data));
+    assert_eq!("true", render(&handlebars, "{{eq missing null}}", &data));
+    assert_eq!("false", render(&handlebars, "{{eq true \"true\"}}", &data));
+    assert_eq!("yes", render(&handlebars, "{{#if (eq string \"1\")}}yes{{else}}no{{/if}}", &data));
+
+    assert!(handlebars.render_template("{{eq 1}}", &data).is_err());
+}
+
+#[test]
+fn ordering() {
+    let handlebars = handlebars(HelperSet::COMPARE);
+
+    let data = json!({
+        "negative": -1,
+        "big": u64::MAX,
+        "float": 1.5,
+    });
+
+    assert_eq!("true", render(&handlebars, "{{gt 2 1}}", &data));
+    assert_eq!("false", render(&handlebars, "{{gt 1 1}}", &data));
+    assert_eq!("true", render(&handlebars, "{{gte 1 1}}", &data));
+    assert_eq!("true", render(&handlebars, "{{lt float 2}}", &data));
+    assert_eq!("true", render(&handlebars, "{{lte 1 float}}", &data));
+    assert_eq!("true", render(&handlebars, "{{gt big negative}}", &data));
+    assert_eq!("true", render(&handlebars, "{{lt negative big}}", &data));
+    assert_eq!("true", render(&handlebars, "{{lt \"apple\" \"banana\"}}", &data));
+    assert_eq!("true", render(&handlebars, "{{gt true false}}", &data));
+
+    assert!(handlebars.render_template("{{gt 1 \"1\"}}", &data).is_err());
+    assert!(handlebars.render_template("{{lt missing 1}}", &data).is_err());
+}
+
+#[test]
+fn membership() {
+    let handlebars = handlebars(HelperSet::COMPARE);
+
+    let data = json!({
+        "tags": ["rust", "web", 3],
+        "roles": {"admin": true},
+        "title": "Hello, world",
+    });
+
+    assert_eq!("true", render(&handlebars, "{{in \"web\" tags}}", &data));
+    assert_eq!("true", render(&handlebars, "{{in 3.0 tags}}", &data));
+    assert_eq!("false", render(&handlebars, "{{in \"go\" tags}}", &data));
+    assert_eq!("true", render(&handlebars, "{{in \"admin\" roles}}", &data));
+    assert_eq!("false", render(&handlebars, "{{in \"guest\" roles}}", &data));
+    assert_eq!("true", render(&handlebars, "{{in \"world\" title}}", &data));
+    assert_eq!("false", render(&handlebars, "{{in \"web\" missing}}", &data));
+
+    assert!(handlebars.render_template("{{in 1 2}}", &data).is_err());
+}
+
+#[test]
+fn logic() {
+    let handlebars = handlebars(HelperSet::LOGIC | HelperSet::COMPARE);
+
+    let data = json!({
+        "zero": 0,
+        "empty": "",
+        "list": [1],
+        "object": {},
+    });
+
+    assert_eq!("true", render(&handlebars, "{{and true 1 \"a\" list}}", &data));
+    assert_eq!("false", render(&handlebars, "{{and true zero}}", &data));
+    assert_eq!("false", render(&handlebars, "{{and true object}}", &data));
+    assert_eq!("true", render(&handlebars, "{{or zero empty list}}", &data));
+    assert_eq!("false", render(&handlebars, "{{or zero empty missing}}", &data));
+    assert_eq!("true", render(&handlebars, "{{not empty}}", &data));
+    assert_eq!("false", render(&handlebars, "{{not list}}", &data));
+    assert_eq!(
+        "yes",
+        render(&handlebars, "{{#if (and (gt 2 1) (not zero))}}yes{{else}}no{{/if}}", &data)
+    );
+
+    assert!(handlebars.render_template("{{and}}", &data).is_err());
+    assert!(handlebars.render_template("{{not}}", &data).is_err());
+}
+
+#[test]
+fn default_and_coalesce() {
+    let handlebars = handlebars(HelperSet::LOGIC);
+
+    let data = json!({
+        "zero": 0,
+        "empty": "",
+        "name": "Alice",
+        "nothing": null,
+    });
+
+    assert_eq!("Alice", render(&handlebars, "{{default name \"Guest\"}}", &data));
+    assert_eq!("Guest", render(&handlebars, "{{default missing \"Guest\"}}", &data));
+    assert_eq!("Guest", render(&handlebars, "{{default empty \"Guest\"}}", &data));
+    assert_eq!("0", render(&handlebars, "{{default zero 1}}", &data));
+    assert_eq!("Alice", render(&handlebars, "{{coalesce nothing missing name}}", &data));
+    assert_eq!("", render(&handlebars, "{{coalesce nothing missing}}", &data));
+    assert_eq!("", render(&handlebars, "{{coalesce empty name}}", &data));
+}
+
+#[test]
+fn arithmetic() {
+    let handlebars = handlebars(HelperSet::MATH | HelperSet::COMPARE);
+
+    let data = json!({
+        "a": 7,
+        "b": 2,
+        "f": 0.5,
+        "big": i64::MAX,
+    });
+
+    assert_eq!("9", render(&handlebars, "{{add a b}}", &data));
+    assert_eq!("10", render(&handlebars, "{{add a b 1}}", &data));
+    assert_eq!("7.5", render(&handlebars, "{{add a f}}", &data));
+    assert_eq!("5", render(&handlebars, "{{sub a b}}", &data));
+    assert_eq!("-5", render(&handlebars, "{{sub b a}}", &data));
+    assert_eq!("14", render(&handlebars, "{{mul a b}}", &data));
+    assert_eq!("3.5", render(&handlebars, "{{mul a f}}", &data));
+    assert_eq!("3.5", render(&handlebars, "{{div a b}}", &data));
+    assert_eq!("4", render(&handlebars, "{{div 8 b}}", &data));
+    assert_eq!("1", render(&handlebars, "{{mod a b}}", &data));
+    assert_eq!("-1", render(&handlebars, "{{mod -7 b}}", &data));
+    assert_eq!("1.5", render(&handlebars, "{{mod 5.5 b}}", &data));
+    assert_eq!("2", render(&handlebars, "{{min a b 3}}", &data));
+    assert_eq!("7", render(&handlebars, "{{max a b 3}}", &data));
+    assert_eq!("0.5", render(&handlebars, "{{min a f}}", &data));
+    assert_eq!("7", render(&handlebars, "{{max a f}}", &data));
+    assert_eq!("2.0", render(&handlebars, "{{min 2.0 3}}", &data));
+    assert_eq!("3", render(&handlebars, "{{max 2.5 3 2.0}}", &data));
+    assert_eq!("9.223372036854776e+18", render(&handlebars, "{{add big 1}}", &data));
+    assert_eq!("true", render(&handlebars, "{{eq (add 0.1 0.2) (add 0.2 0.1)}}", &data));
+
+    assert!(handlebars.render_template("{{div a 0}}", &data).is_err());
+    assert!(handlebars.render_template("{{mod a 0.0}}", &data).is_err());
+    assert!(handlebars.render_template("{{add a \"1\"}}", &data).is_err());
+    assert!(handlebars.render_template("{{sub a}}", &data).is_err());
+}
+
+#[test]
+fn rounding() {
+    let handlebars = handlebars(HelperSet::MATH);
+
+    assert_eq!("3", render(&handlebars, "{{round 2.5}}", &json!({})));
+    assert_eq!("-3", render(&handlebars, "{{round -2.5}}", &json!({})));
+    assert_eq!("2.46", render(&handlebars, "{{round 2.456 decimals=2}}", &json!({})));
+    assert_eq!("7", render(&handlebars, "{{round 7 decimals=2}}", &json!({})));
+    assert_eq!(
+        "1.2e-17",
+        render(&handlebars, "{{round x decimals=18}}", &json!({ "x": 1.23e-17 }))
+    );
+    assert_eq!("1e+300", render(&handlebars, "{{round x decimals=20}}", &json!({ "x": 1e300 })));
+
+    assert!(handlebars.render_template("{{round 2.5 decimals=-1}}", &json!({})).is_err());
+    assert!(handlebars.render_template("{{round 2.5 decimals=21}}", &json!({})).is_err());
+}
+
+#[test]
+fn number_formatting() {
+    let handlebars = handlebars(HelperSet::MATH);
+
+    let data = json!({});
+
+    assert_eq!(
+        "1,234,567.80",
+        render(&handlebars, "{{format_number 1234567.8 decimals=2 sep=\",\"}}", &data)
+    );
+    assert_eq!("1,234,567.8", render(&handlebars, "{{format_number 1234567.8}}", &data));
+    assert_eq!("-1,000", render(&handlebars, "{{format_number -1000}}", &data));
+    assert_eq!("999", render(&handlebars, "{{format_number 999}}", &data));
+    assert_eq!(
+        "1.234.567,00",
+        render(&handlebars, "{{format_number 1234567 decimals=2 sep=\".\" point=\",\"}}", &data)
+    );
+    assert_eq!("100000", render(&handlebars, "{{format_number 100000 sep=\"\"}}", &data));
+
+    assert_eq!("26%", render(&handlebars, "{{percent 0.256}}", &data));
+    assert_eq!("25.6%", render(&handlebars, "{{percent 0.256 decimals=1}}", &data));
+    assert_eq!("100%", render(&handlebars, "{{percent 1}}", &data));
+
+    assert_eq!("512 B", render(&handlebars, "{{bytes 512}}", &data));
+    assert_eq!("1.5 KiB", render(&handlebars, "{{bytes 1536}}", &data));
+    assert_eq!("1.00 MiB", render(&handlebars, "{{bytes 1048576 decimals=2}}", &data));
+    assert_eq!("1.5 kB", render(&handlebars, "{{bytes 1500 si=true}}", &data));
+    assert_eq!("2 GB", render(&handlebars, "{{bytes 2000000000 si=true decimals=0}}", &data));
+
+    assert!(handlebars.render_template("{{format_number \"1\"}}", &data).is_err());
+    assert!(handlebars.render_template("{{bytes 1 si=1}}", &data).is_err());
+    assert!(handlebars.render_template("{{percent 1 decimals=1000000000}}", &data).is_err());
+    assert!(handlebars.render_template("{{format_number 1 decimals=21}}", &data).is_err());
+}
+
+#[cfg(feature = "helper_string")]
+#[test]
+fn case_and_trim() {
+    let handlebars = handlebars(HelperSet::STRING);
+
+    let data = json!({
+        "name": "  straße  ",
+        "title": "émile zola",
+    });
+
+    assert_eq!("STRASSE", render(&handlebars, "{{upper (trim name)}}", &data));
+    assert_eq!("straße", render(&handlebars, "{{lower \"STRAßE\"}}", &data));
+    assert_eq!("Émile zola", render(&handlebars, "{{capitalize title}}", &data));
+    assert_eq!("", render(&handlebars, "{{capitalize missing}}", &data));
+    assert_eq!("42", render(&handlebars, "{{upper 42}}", &data));
+
+    assert!(handlebars.render_template("{{upper (split \"a\")}}", &data).is_err());
+}
+
+#[cfg(feature = "helper_string")]
+#[test]
+fn truncate_and_pad() {
+    let handlebars = handlebars(HelperSet::STRING);
+
+    let data = json!({
+        "text": "Hello, world",
+        "flags": "🇯🇵🇹🇼🇺🇸",
+        "accent": "e\u{301}e\u{301}e\u{301}",
+        "html": "<b>bold</b>",
+    });
+
+    assert_eq!("Hello…", render(&handlebars, "{{truncate text len=5}}", &data));
+    assert_eq!("Hello...", render(&handlebars, "{{truncate text 5 suffix=\"...\"}}", &data));
+    assert_eq!("Hello, world", render(&handlebars, "{{truncate text len=12}}", &data));
+    assert_eq!("🇯🇵🇹🇼…", render(&handlebars, "{{truncate flags len=2}}", &data));
+    assert_eq!("e\u{301}…", render(&handlebars, "{{truncate accent len=1}}", &data));
+    assert_eq!("&lt;b&gt;…", render(&handlebars, "{{truncate html len=3}}", &data));
+    assert_eq!("<b>…", render(&handlebars, "{{{truncate html len=3}}}", &data));
+
+    assert_eq!("007", render(&handlebars, "{{pad 7 len=3 char=\"0\" side=\"start\"}}", &data));
+    assert_eq!("ab  ", render(&handlebars, "{{pad \"ab\" 4}}", &data));
+    assert_eq!("-ab--", render(&handlebars, "{{pad \"ab\" 5 char=\"-\" side=\"both\"}}", &data));
+    assert_eq!("🇯🇵🇹🇼🇺🇸", render(&handlebars, "{{pad flags 3}}", &data));
+
+    assert!(handlebars.render_template("{{truncate text}}", &data).is_err());
+    assert!(handlebars.render_template("{{pad text 20 char=\"ab\"}}", &data).is_err());
+    assert!(handlebars.render_template("{{pad text 20 side=\"left\"}}", &data).is_err());
+}
+
+#[cfg(feature = "helper_string")]
+#[test]
+fn slugify_and_replace() {
+    let handlebars = handlebars(HelperSet::STRING);
+
+    let data = json!({});
+
+    assert_eq!(
+        "hello-world-2024",
+        render(&handlebars, "{{slugify \" Hello, World! 2024 \"}}", &data)
+    );
+    assert_eq!("grüße-welt", render(&handlebars, "{{slugify \"Grüße, Welt!\"}}", &data));
+    assert_eq!("", render(&handlebars, "{{slugify \"--\"}}", &data));
+    assert_eq!("a-b-c", render(&handlebars, "{{replace \"a.b.c\" \".\" \"-\"}}", &data));
+    assert_eq!("abc", render(&handlebars, "{{replace \"abc\" \"\" \"-\"}}", &data));
+}
+
+#[cfg(feature = "helper_string")]
+#[test]
+fn split_join_and_concat() {
+    let handlebars = handlebars(HelperSet::STRING);
+
+    let data = json!({
+        "csv": "a,b,c",
+        "items": ["x", 1, true, null],
+    });
+
+    assert_eq!("[a][b][c]", render(&handlebars, "{{#each (split csv)}}[{{this}}]{{/each}}", &data));
+    assert_eq!("a | b | c", render(&handlebars, "{{join (split csv) sep=\" | \"}}", &data));
+    assert_eq!("x,1,true,", render(&handlebars, "{{join items}}", &data));
+    assert_eq!(
+        "[é][🇯🇵]",
+        render(&handlebars, "{{#each (split \"é🇯🇵\" sep=\"\")}}[{{this}}]{{/each}}", &data)
+    );
+    assert_eq!("a1true", render(&handlebars, "{{concat \"a\" 1 true missing}}", &data));
+    assert_eq!("&lt;p&gt;", render(&handlebars, "{{concat \"<\" \"p>\"}}", &data));
+
+    assert!(handlebars.render_template("{{join csv}}", &data).is_err());
+    assert!(handlebars.render_template("{{concat}}", &data).is_err());
+}
+
+#[cfg(feature = "helper_collection")]
+#[test]
+fn len_first_last_and_slice() {
+    let handlebars = handlebars(HelperSet::COLLECTION);
+
+    let data = json!({
+        "list": [1, 2, 3, 4, 5],
+        "empty": [],
+        "object": {"a": 1, "b": 2},
+        "flags": "🇯🇵🇹🇼",
+    });
+
+    assert_eq!("5", render(&handlebars, "{{len list}}", &data));
+    assert_eq!("2", render(&handlebars, "{{len object}}", &data));
+    assert_eq!("2", render(&handlebars, "{{len flags}}", &data));
+    assert_eq!("0", render(&handlebars, "{{len missing}}", &data));
+
+    assert_eq!("1", render(&handlebars, "{{first list}}", &data));
+    assert_eq!("5", render(&handlebars, "{{last list}}", &data));
+    assert_eq!("", render(&handlebars, "{{first empty}}", &data));
+    assert_eq!("12", render(&handlebars, "{{#each (first list 2)}}{{this}}{{/each}}", &data));
+    assert_eq!("45", render(&handlebars, "{{#each (last list 2)}}{{this}}{{/each}}", &data));
+    assert_eq!("12345", render(&handlebars, "{{#each (last list 9)}}{{this}}{{/each}}", &data));
+
+    assert_eq!("234", render(&handlebars, "{{#each (slice list 1 4)}}{{this}}{{/each}}", &data));
+    assert_eq!("45", render(&handlebars, "{{#each (slice list -2)}}{{this}}{{/each}}", &data));
+    assert_eq!("1234", render(&handlebars, "{{#each (slice list 0 -1)}}{{this}}{{/each}}", &data));
+    assert_eq!("", render(&handlebars, "{{#each (slice list 4 2)}}{{this}}{{/each}}", &data));
+
+    // unlike the built-in `len`, which counts bytes
+    let built_in = Handlebars::new();
+
+    assert_eq!("16", render(&built_in, "{{len flags}}", &data));
+    assert_eq!("5", render(&built_in, "{{len \"hello\"}}", &data));
+    assert_eq!("5", render(&handlebars, "{{len \"hello\"}}", &data));
+
+    assert!(handlebars.render_template("{{len 1}}", &data).is_err());
+    assert!(handlebars.render_template("{{first object}}", &data).is_err());
+    assert!(handlebars.render_template("{{first list -1}}", &data).is_err());
+}
+
+#[cfg(feature = "helper_collection")]
+#[test]
+fn sort_and_group() {
+    let handlebars = handlebars(HelperSet::COLLECTION);
+
+    let data = json!({
+        "posts": [
+            {"title": "b", "year": 2023, "author": {"name": "Zoe"}},
+            {"title": "a", "year": 2024, "author": {"name": "Adam"}},
+            {"title": "c", "year": 2023},
+            {"title": "d", "year": 2024, "author": {"name": "Bob"}},
+        ],
+        "numbers": [3, 1.5, 2],
+        "big": [9007199254740993u64, 9007199254740992.0, 9007199254740992u64],
+        "mixed": [1, "a"],
+        "many_mixed": (0..64).map(|i| if i % 2 == 0 { json!(i) } else { json!("a") }).collect::<Vec<_>>(),
+    });
+
+    assert_eq!(
+        "abcd",
+        render(&handlebars, "{{#each (sort_by posts \"title\")}}{{title}}{{/each}}", &data)
+    );
+    assert_eq!(
+        "dcba",
+        render(
+            &handlebars,
+            "{{#each (sort_by posts \"title\" desc=true)}}{{title}}{{/each}}",
+            &data
+        )
+    );
+    assert_eq!(
+        "adbc",
+        render(&handlebars, "{{#each (sort_by posts \"author.name\")}}{{title}}{{/each}}", &data)
+    );
+    assert_eq!(
+        "bcad",
+        render(&handlebars, "{{#each (sort_by posts field=\"year\")}}{{title}}{{/each}}", &data)
+    );
+    assert_eq!(
+        "1.5,2,3,",
+        render(&handlebars, "{{#each (sort_by numbers)}}{{this}},{{/each}}", &data)
+    );
+    assert_eq!(
+        "9007199254740992.0,9007199254740992,9007199254740993,",
+        render(&handlebars, "{{#each (sort_by big)}}{{this}},{{/each}}", &data)
+    );
+
+    assert_eq!(
+        "2023:bc;2024:ad;",
+        render(
+            &handlebars,
+            "{{#each (group_by posts \"year\")}}{{key}}:{{#each \
+             items}}{{title}}{{/each}};{{/each}}",
+            &data
+        )
+    );
+
+    assert!(handlebars.render_template("{{sort_by mixed}}", &data).is_err());
+    assert!(handlebars.render_template("{{sort_by many_mixed}}", &data).is_err());
+    assert!(handlebars.render_template("{{sort_by posts \"author\"}}", &data).is_err());
+    assert!(handlebars.render_template("{{sort_by posts 1}}", &data).is_err());
+}
+
+#[cfg(feature = "helper_collection")]
+#[test]
+fn range_contains_keys_and_values() {
+    let handlebars = handlebars(HelperSet::COLLECTION);
+
+    let data = json!({
+        "tags": ["rust", "web"],
+        "object": {"b": 1, "a": [2]},
+    });
+
+    assert_eq!("1234", render(&handlebars, "{{#each (range 1 5)}}{{this}}{{/each}}", &data));
+    assert_eq!(
+        "0,3,6,9,",
+        render(&handlebars, "{{#each (range 0 10 3)}}{{this}},{{/each}}", &data)
+    );
+    assert_eq!("5,3,", render(&handlebars, "{{#each (range 5 1 -2)}}{{this}},{{/each}}", &data));
+    assert_eq!("", render(&handlebars, "{{#each (range 5 1)}}{{this}}{{/each}}", &data));
+
+    assert_eq!("true", render(&handlebars, "{{contains tags \"web\"}}", &data));
+    assert_eq!("false", render(&handlebars, "{{contains tags \"go\"}}", &data));
+    assert_eq!("true", render(&handlebars, "{{contains object \"a\"}}", &data));
+    assert_eq!("true", render(&handlebars, "{{contains \"rustacean\" \"rust\"}}", &data));
+
+    assert_eq!("a,b,", render(&handlebars, "{{#each (keys object)}}{{this}},{{/each}}", &data));
+    assert_eq!("1", render(&handlebars, "{{last (values object)}}", &data));
+    assert_eq!("", render(&handlebars, "{{#each (keys missing)}}{{this}}{{/each}}", &data));
+
+    assert!(handlebars.render_template("{{range 1 5 0}}", &data).is_err());
+    assert!(handlebars.render_template("{{range 0 1000000000}}", &data).is_err());
+    assert!(handlebars.render_template("{{keys tags}}", &data).is_err());
+}
+
+#[test]
+fn json() {
+    let handlebars = handlebars(HelperSet::JSON);
+
+    let data = json!({
+        "data": {"html": "</script><!-- & -->", "separators": "\u{2028}\u{2029}", "n": 1},
+        "list": [1, "<b>"],
+        "quote": "\"a\"",
+    });
+
+    let rendered = render(&handlebars, "{{{json data}}}", &data);
+
+    assert_eq!(
+        r#"{"html":"\u003c/script\u003e\u003c!-- \u0026 --\u003e","n":1,"separators":"\u2028\u2029"}"#,
+        rendered
+    );
+    assert_eq!(data["data"], serde_json::from_str::<Value>(&rendered).unwrap());
+
+    assert_eq!("&quot;\\&quot;a\\&quot;&quot;", render(&handlebars, "{{json quote}}", &data));
+    assert_eq!("null", render(&handlebars, "{{{json missing}}}", &data));
+    assert_eq!(
+        "[\n  1,\n  \"\\u003cb\\u003e\"\n]",
+        render(&handlebars, "{{{json_pretty list}}}", &data)
+    );
+
+    assert!(handlebars.render_template("{{json}}", &data).is_err());
+}
+
+#[test]
+fn pagination() {
+    let pagination = Pagination::new(6, 10, 95).with_url("/posts?page={page}");
+
+    assert_eq!(10, pagination.total_pages());
+    assert_eq!(50, pagination.offset());
+    assert_eq!(Some(5), pagination.prev());
+    assert_eq!(Some(7), pagination.next());
+    assert_eq!(
+        vec![Some(1), None, Some(4), Some(5), Some(6), Some(7), Some(8), Some(9), Some(10)],
+        pagination.pages(2)
+    );
+
+    // a gap is never in place of only one page
+    assert_eq!(
+        vec![Some(1), Some(2), Some(3), Some(4), None, Some(10)],
+        Pagination::new(2, 10, 95).pages(2)
+    );
+    assert!(Pagination::new(1, 10, 0).pages(2).is_empty());
+
+    let value = serde_json::to_value(&pagination).unwrap();
+
+    assert_eq!(json!(10), value["total_pages"]);
+    assert_eq!(json!("/posts?page=5"), value["prev_url"]);
+    assert_eq!(json!("/posts?page=7"), value["next_url"]);
+    assert_eq!(Value::Null, serde_json::to_value(Pagination::new(1, 10, 5)).unwrap()["prev"]);
+}
+
+#[test]
+fn paginate() {
+    let handlebars = handlebars(HelperSet::PAGINATION);
+
+    let template = "{{#paginate pagination window=1}}{{#if is_gap}}…{{else}}{{#if \
+                    is_current}}[{{number}}]{{else}}<{{{url}}}>{{/if}}{{/if}}{{#unless @last}} \
+                    {{/unless}}{{else}}none{{/paginate}}";
+
+    assert_eq!(
+        "<?page=1> … <?page=4> [5] <?page=6> … <?page=10>",
+        render(&handlebars, template, &json!({ "pagination": Pagination::new(5, 10, 95) }))
+    );
+
+    assert_eq!(
+        "[1] </p/2> </p/3>",
+        render(
+            &handlebars,
+            "{{#paginate pagination url=\"/p/{page}\" as |item|}}{{#if \
+             item.is_current}}[{{item.number}}]{{else}}<{{item.url}}>{{/if}}{{#unless @last}} \
+             {{/unless}}{{/paginate}}",
+            &json!({ "pagination": Pagination::new(1, 10, 30) })
+        )
+    );
+
+    assert_eq!(
+        "none",
+        render(&handlebars, template, &json!({ "pagination": Pagination::new(1, 10, 7) }))
+    );
+}
+
+#[cfg(feature = "helper_date")]
+#[test]
+fn parse_and_format() {
+    let handlebars = date_handlebars();
+
+    let data = json!({
+        "rfc3339": "2024-05-01T12:30:45+08:00",
+        "iso8601": "2024-05-01T12:30:45+0800",
+        "naive": "2024-05-01T12:30:45",
+        "date": "2024-05-01",
+        "unix": 1714566645,
+        "float": 1714566645.5,
+    });
+
+    assert_eq!(
+        "2024-05-01 12:30",
+        render(&handlebars, "{{format_date rfc3339 \"%Y-%m-%d %H:%M\"}}", &data)
+    );
+    assert_eq!(
+        "2024-05-01 12:30",
+        render(&handlebars, "{{format_date iso8601 \"%Y-%m-%d %H:%M\"}}", &data)
+    );
+    assert_eq!("2024-05-01T12:30:45+00:00", render(&handlebars, "{{format_date naive}}", &data));
+    assert_eq!("2024-05-01T00:00:00+00:00", render(&handlebars, "{{format_date date}}", &data));
+    assert_eq!("2024-05-01T12:30:45+00:00", render(&handlebars, "{{format_date unix}}", &data));
+    assert_eq!(
+        "12:30:45.500",
+        render(&handlebars, "{{format_date float \"%H:%M:%S%.3f\"}}", &data)
+    );
+    assert_eq!("1714537845", render(&handlebars, "{{timestamp rfc3339}}", &data));
+
+    assert!(handlebars.render_template("{{format_date \"yesterday\"}}", &data).is_err());
+    assert!(handlebars.render_template("{{format_date true}}", &data).is_err());
+    assert!(handlebars.render_template("{{format_date unix \"%Q\"}}", &data).is_err());
+}
+
+#[cfg(feature = "helper_date")]
+#[test]
+fn timezones() {
+    let handlebars = date_handlebars();
+
+    let data = json!({
+        "date": "2024-01-15T12:00:00Z",
+    });
+
+    assert_eq!(
+        "2024-01-15 13:00 CET",
+        render(
+            &handlebars,
+            "{{format_date date \"%Y-%m-%d %H:%M %Z\" tz=\"Europe/Berlin\"}}",
+            &data
+        )
+    );
+    assert_eq!("20:00", render(&handlebars, "{{format_date date \"%H:%M\" tz=\"+08:00\"}}", &data));
+    assert_eq!(
+        "2024-01-15T07:00:00-05:00",
+        render(&handlebars, "{{to_timezone date \"America/New_York\"}}", &data)
+    );
+    assert_eq!("2024-01-15T12:00:00Z", render(&handlebars, "{{to_timezone date \"UTC\"}}", &data));
+    assert_eq!(
+        "21:00",
+        render(&handlebars, "{{format_date (to_timezone date \"Asia/Tokyo\") \"%H:%M\"}}", &data)
+    );
+
+    assert!(handlebars.render_template("{{to_timezone date \"Mars/Olympus\"}}", &data).is_err());
+}
+
+#[cfg(feature = "helper_date")]
+#[test]
+fn relative_times() {
+    let handlebars = date_handlebars();
+
+    let data = json!({
+        "seconds": "2024-05-01T11:59:30Z",
+        "minute": "2024-05-01T11:59:00Z",
+        "hours": "2024-05-01T09:00:00+00:00",
+        "days": "2024-04-28T12:00:00Z",
+        "year": "2023-04-01",
+        "future": "2024-05-03T12:00:00Z",
+    });
+
+    assert_eq!("just now", render(&handlebars, "{{time_ago seconds}}", &data));
+    assert_eq!("1 minute ago", render(&handlebars, "{{time_ago minute}}", &data));
+    assert_eq!("3 hours ago", render(&handlebars, "{{time_ago hours}}", &data));
+    assert_eq!("3 days ago", render(&handlebars, "{{time_ago days}}", &data));
+    assert_eq!("1 year ago", render(&handlebars, "{{time_ago year}}", &data));
+    assert_eq!("in 2 days", render(&handlebars, "{{time_ago future}}", &data));
+    assert_eq!("2024-05-01T12:00:00Z", render(&handlebars, "{{now}}", &data));
+    assert_eq!("2024", render(&handlebars, "{{format_date (now) \"%Y\"}}", &data));
+}
+
+#[cfg(feature = "helper_markdown")]
+#[test]
+fn markdown() {
+    let handlebars = handlebars(HelperSet::MARKDOWN);
+
+    let data = json!({
+        "body": "# Title\n\nSome *emphasis* and [a link](https://example.com).",
+        "table": "| a | b |\n|---|---|\n| 1 | 2 |",
+    });
+
+    assert_eq!(
+        "<h1>Title</h1>\n<p>Some <em>emphasis</em> and <a href=\"https://example.com\" \
+         rel=\"noopener noreferrer\">a link</a>.</p>\n",
+        render(&handlebars, "{{markdown body}}", &data)
+    );
+    assert_eq!("", render(&handlebars, "{{markdown missing}}", &data));
+    assert!(!render(&handlebars, "{{markdown table}}", &data).contains("<table>"));
+    assert!(render(&handlebars, "{{markdown table tables=true}}", &data).contains("<table>"));
+    assert_eq!(
+        "<p><del>old</del></p>\n",
+        render(&handlebars, "{{markdown \"~~old~~\" strikethrough=true}}", &data)
+    );
+
+    assert!(handlebars.render_template("{{markdown 1}}", &data).is_err());
+    assert!(handlebars.render_template("{{markdown}}", &data).is_err());
+    assert!(handlebars.render_template("{{markdown body tables=1}}", &data).is_err());
+}
+
+#[cfg(feature = "helper_markdown")]
+#[test]
+fn markdown_block() {
+    let handlebars = handlebars(HelperSet::MARKDOWN);
+
+    let data = json!({
+        "name": "<World>",
+    });
+
+    assert_eq!(
+        "<h2>Hello, &lt;World&gt;</h2>\n<ul>\n<li>one</li>\n<li>two</li>\n</ul>\n",
+        render(
+            &handlebars,
+            "{{#markdown}}\n    ## Hello, {{name}}\n\n    * one\n    * two\n{{/markdown}}",
+            &data
+        )
+    );
+}
+
+#[cfg(feature = "helper_markdown")]
+#[test]
+fn markdown_sanitization() {
+    let handlebars = handlebars(HelperSet::MARKDOWN);
+
+    let data = json!({
+        "body": "Hi <script>alert(1)</script><img src=\"x.png\" onerror=\"alert(2)\"> \
+                 [click](javascript:alert(3))",
+    });
+
+    let html = render(&handlebars, "{{markdown body}}", &data);
+
+    assert!(!html.contains("script"));
+    assert!(!html.contains("onerror"));
+    assert!(!html.contains("javascript:"));
+    assert!(html.contains("<img src=\"x.png\">"));
+}
+
+#[cfg(feature = "helper_sanitize")]
+#[test]
+fn sanitize() {
+    let handlebars = handlebars(HelperSet::SANITIZE);
+
+    let data = json!({
+        "bio": "<p onclick=\"alert(1)\">Hi <b>there</b><script>alert(2)</script></p>\
+                <a href=\"javascript:alert(3)\">x</a>",
+    });
+
+    assert_eq!(
+        "<p>Hi <b>there</b></p><a rel=\"noopener noreferrer\">x</a>",
+        render(&handlebars, "{{{sanitize bio}}}", &data)
+    );
+    assert_eq!("&lt;b&gt;x&lt;/b&gt;", render(&handlebars, "{{sanitize \"<b>x</b>\"}}", &data));
+    assert_eq!("", render(&handlebars, "{{{sanitize missing}}}", &data));
+
+    assert!(handlebars.render_template("{{sanitize}}", &data).is_err());
+    assert!(handlebars.render_template("{{sanitize (bio)}}", &json!({"bio": []})).is_err());
+}
+
+#[cfg(feature = "helper_sanitize")]
+#[test]
+fn custom_allow_list() {
+    let mut handlebars = handlebars(HelperSet::SANITIZE);
+
+    let mut builder = ammonia::Builder::empty();
+
+    builder
+        .add_tags(["b", "a"])
+        .add_tag_attributes("a", ["href"])
+        .rm_generic_attributes(["title"])
+        .link_rel(None);
+
+    register_sanitize_helper(&mut handlebars, builder);
+
+    let data = json!({
+        "bio": "<p>Hi <b>there</b>, <a href=\"https://example.com\" title=\"t\">x</a></p>",
+    });
+
+    assert_eq!(
+        "Hi <b>there</b>, <a href=\"https://example.com\">x</a>",
+        render(&handlebars, "{{{sanitize bio}}}", &data)
+    );
+}
+
+#[cfg(feature = "helper_highlight")]
+#[test]
+fn highlight() {
+    let handlebars = handlebars(HelperSet::HIGHLIGHT);
+
+    let html = render(
+        &handlebars,
+        "{{#highlight lang=\"rust\"}}\n    fn main() {\n        println!(\"<{}>\", 1);\n    \
+         }\n{{/highlight}}",
+        &json!({}),
+    );
+
+    assert!(html.starts_with(
+        "<pre class=\"hl\"><code class=\"language-rust\"><span class=\"hl-source hl-rust\">"
+    ));
+    assert!(html.contains("<span class=\"hl-storage hl-type hl-function hl-rust\">fn</span>"));
+    assert!(html
+        .contains("&lt;<span class=\"hl-constant hl-other hl-placeholder hl-rust\">{}</span>&gt;"));
+    assert!(html.contains("\n    <span"));
+    assert!(!html.contains("<script"));
+    assert!(html.ends_with("</code></pre>"));
+
+    assert_eq!(
+        "<pre class=\"hl\"><code class=\"language-nope\"><span class=\"hl-text hl-plain\">a &lt; \
+         b\n</span></code></pre>",
+        render(&handlebars, "{{highlight code lang=\"nope\"}}", &json!({ "code": "a < b" }))
+    );
+
+    assert!(render(&handlebars, "{{#highlight}}let a = \\{{b}};{{/highlight}}", &json!({}))
+        .contains("{{b}}"));
+
+    assert!(highlight_css("InspiredGitHub").unwrap().contains(".hl-source"));
+    assert!(highlight_css("missing").is_none());
+}
+
+#[cfg(all(feature = "helper_highlight", feature = "helper_markdown"))]
+#[test]
+fn markdown_code_blocks() {
+    let handlebars = handlebars(HelperSet::MARKDOWN);
+
+    let html = render(
+        &handlebars,
+        "{{markdown body}}",
+        &json!({ "body": "```rust,ignore\nlet x = 1;\n```\n\n```\nplain <b>\n```\n\n<span class=\"x\" onclick=\"a()\">y</span>" }),
+    );
+
+    assert!(html.contains(
+        "<pre class=\"hl\"><code class=\"language-rust\"><span class=\"hl-source hl-rust\">"
+    ));
+    assert!(html.contains("<pre><code>plain &lt;b&gt;\n</code></pre>"));
+    assert!(html.contains("<span class=\"x\">y</span>"));
+}
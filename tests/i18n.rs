@@ -0,0 +1,190 @@
+#![cfg(feature = "i18n")]
+
+use rocket::{http::Header, local::blocking::Client, State};
+use rocket_include_handlebars::{fluent_bundle::FluentArgs, *};
+use serde_json::json;
+
+const EN_US: &str = include_str!("i18n/en-US.ftl");
+const DE: &str = include_str!("i18n/de.ftl");
+const DE_AT: &str = include_str!("i18n/de-AT.ftl");
+
+fn translations() -> Translations {
+    let mut translations = Translations::new("en-US").unwrap();
+
+    translations.insert_str("en-US", EN_US).unwrap();
+    translations.insert_str("de", DE).unwrap();
+    translations.insert_str("de-AT", DE_AT).unwrap();
+
+    translations
+}
+
+#[test]
+fn fallbacks() {
+    let translations = translations();
+
+    let negotiated = |tag: &str| {
+        translations
+            .negotiate(&Locale::new(tag))
+            .into_iter()
+            .map(|locale| locale.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(vec!["de-AT", "de", "en-US"], negotiated("de-AT"));
+    assert_eq!(vec!["de", "de-AT", "en-US"], negotiated("de"));
+    assert_eq!(vec!["de", "de-AT", "en-US"], negotiated("de-CH"));
+    assert_eq!(vec!["en-US"], negotiated("fr"));
+
+    let at = Locale::new("de-AT");
+
+    assert_eq!("Einloggen", translations.format(&at, "login", None).unwrap());
+    assert_eq!("Ihre E-Mail", translations.format(&at, "login.placeholder", None).unwrap());
+    assert_eq!("Only in English", translations.format(&at, "only-english", None).unwrap());
+    assert_eq!(None, translations.format(&at, "missing", None));
+
+    let mut args = FluentArgs::new();
+
+    args.set("count", 1);
+
+    assert_eq!(
+        "Sie haben eine ungelesene Nachricht.",
+        translations.format(&at, "unread", Some(&args)).unwrap()
+    );
+}
+
+#[test]
+fn invalid_resource() {
+    let mut translations = Translations::new("en-US").unwrap();
+
+    assert!(translations.insert_str("en-US", "= no id").is_err());
+    assert!(translations.insert_str("not a locale", EN_US).is_err());
+    assert!(Translations::new("not a locale").is_err());
+}
+
+#[rocket::get("/")]
+fn page(
+    cm: &State<HandlebarsContextManager>,
+    etag_if_none_match: EtagIfNoneMatch,
+    locale: Locale,
+) -> HandlebarsResponse {
+    cm.build_localized(
+        &etag_if_none_match,
+        false,
+        &locale,
+        "page",
+        json!({ "user": { "name": "Ada" }, "count": 3 }),
+    )
+}
+
+#[rocket::get("/cached")]
+fn cached_page(
+    cm: &State<HandlebarsContextManager>,
+    etag_if_none_match: EtagIfNoneMatch,
+    locale: Locale,
+) -> HandlebarsResponse {
+    handlebars_response_cache!(
+        localized cm,
+        etag_if_none_match,
+        locale,
+        "page",
+        "page",
+        json!({ "user": { "name": "Ada" }, "count": 3 })
+    )
+}
+
+fn client() -> Client {
+    let rocket = rocket::build().mount("/", rocket::routes![page, cached_page]).attach(
+        HandlebarsResponse::fairing(|handlebars| {
+            handlebars_translations_initialize!(
+                handlebars,
+                "en-US",
+                "en-US" => "tests/i18n/en-US.ftl",
+                "de" => "tests/i18n/de.ftl",
+                "de-AT" => "tests/i18n/de-AT.ftl",
+            );
+
+            handlebars
+                .register_template_string(
+                    "page",
+                    "<html lang=\"{{locale}}\">{{t \"welcome-user\" name=user.name}} {{t \
+                     \"unread\" count=count}}</html>",
+                )
+                .unwrap();
+        }),
+    );
+
+    Client::untracked(rocket).unwrap()
+}
+
+#[test]
+fn fairing() {
+    let client = client();
+
+    let get = |accept_language: &str| {
+        client
+            .get("/")
+            .header(Header::new("Accept-Language", accept_language.to_string()))
+            .dispatch()
+            .into_string()
+            .unwrap()
+    };
+
+    assert_eq!(
+        "<html lang=\"en-US\">Welcome, Ada! You have 3 unread messages.</html>",
+        get("fr, en;q=0.5")
+    );
+
+    assert_eq!(
+        "<html lang=\"de-AT\">Willkommen, Ada! Sie haben 3 ungelesene Nachrichten.</html>",
+        get("de-AT")
+    );
+
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    assert_eq!(
+        "<html lang=\"en-US\">Welcome, Ada! You have one unread message.</html>",
+        cm.render("page", json!({ "user": { "name": "Ada" }, "count": 1 }))
+    );
+}
+
+#[test]
+fn localized_cache() {
+    let client = client();
+
+    let get = |accept_language: &str| {
+        client
+            .get("/cached")
+            .header(Header::new("Accept-Language", accept_language.to_string()))
+            .dispatch()
+            .into_string()
+            .unwrap()
+    };
+
+    assert_eq!(
+        "<html lang=\"de-AT\">Willkommen, Ada! Sie haben 3 ungelesene Nachrichten.</html>",
+        get("de-AT")
+    );
+    assert_eq!(
+        "<html lang=\"en-US\">Welcome, Ada! You have 3 unread messages.</html>",
+        get("fr, en;q=0.5")
+    );
+    assert_eq!(
+        "<html lang=\"de-AT\">Willkommen, Ada! Sie haben 3 ungelesene Nachrichten.</html>",
+        get("de-AT, xx-YY;q=0.1")
+    );
+
+    #[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+    {
+        let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+        get("zz");
+        get("en-GB");
+
+        // the keys only vary among the negotiated locales
+        let mut keys: Vec<String> = cm.entries().into_iter().map(|info| info.key).collect();
+
+        keys.sort();
+
+        assert_eq!(vec!["page@de-AT", "page@en-US"], keys);
+    }
+}
@@ -0,0 +1 @@
+login = Einloggen
@@ -0,0 +1,8 @@
+welcome-user = Willkommen, { $name }!
+unread =
+    { $count ->
+        [one] Sie haben eine ungelesene Nachricht.
+       *[other] Sie haben { $count } ungelesene Nachrichten.
+    }
+login = Anmelden
+    .placeholder = Ihre E-Mail
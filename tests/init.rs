@@ -18,3 +18,37 @@ fn fairing() {
         "index2" => "examples/views/index2.hbs"
     ));
 }
+
+#[test]
+fn cache_warming_failure() {
+    let rocket = rocket::build()
+        .attach(handlebars_resources_initializer!(
+            "index" => "examples/views/index.hbs"
+        ))
+        .attach(HandlebarsResponse::cache_warming_fairing(|warmer| {
+            warmer.warm("index", |cm| {
+                Ok(handlebars_response!(cm, EtagIfNoneMatch::default(), "index"))
+            });
+            warmer.warm_required("missing", |cm| {
+                Ok(handlebars_response!(cm, EtagIfNoneMatch::default(), "missing"))
+            });
+        }));
+
+    match rocket::local::blocking::Client::untracked(rocket) {
+        Ok(_) => panic!("the required cache should fail to be warmed up"),
+        Err(err) => assert!(matches!(err.kind(), rocket::error::ErrorKind::FailedFairings(_))),
+    }
+
+    let rocket = rocket::build()
+        .attach(handlebars_resources_initializer!(
+            "index" => "examples/views/index.hbs"
+        ))
+        .attach(HandlebarsResponse::cache_warming_fairing(|warmer| {
+            warmer.warm_required("error", |_| Err("the database is not ready".into()));
+        }));
+
+    match rocket::local::blocking::Client::untracked(rocket) {
+        Ok(_) => panic!("the required cache should fail to be warmed up"),
+        Err(err) => assert!(matches!(err.kind(), rocket::error::ErrorKind::FailedFairings(_))),
+    }
+}
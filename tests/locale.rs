@@ -0,0 +1,185 @@
+use rocket::{http::Cookie, local::blocking::Client};
+use rocket_include_handlebars::*;
+use serde_json::json;
+
+#[test]
+fn parse() {
+    assert_eq!(["de-AT"], Locale::new("de_at").tags());
+    assert_eq!(["zh-Hant-TW"], Locale::new("ZH-hant-tw").tags());
+    assert!(Locale::new("*").is_empty());
+    assert!(Locale::new("de AT").is_empty());
+
+    assert_eq!(
+        ["de-AT", "en", "de"],
+        Locale::from_accept_language("de;q=0.8, fr;q=0, de-AT, *;q=0.5, en;q=0.9, DE-at").tags()
+    );
+
+    assert_eq!(
+        vec!["de-AT", "de", "zh-Hant-TW", "zh-Hant", "zh"],
+        Locale::from_tags(["de-AT", "de", "zh-Hant-TW"]).fallbacks()
+    );
+}
+
+#[rocket::get("/")]
+fn locale(locale: Locale) -> String {
+    locale.tags().join(",")
+}
+
+#[test]
+fn request_guard() {
+    let client = Client::untracked(rocket::build().mount("/", rocket::routes![locale])).unwrap();
+
+    assert_eq!("", client.get("/").dispatch().into_string().unwrap());
+
+    assert_eq!(
+        "de-AT,de,en",
+        client
+            .get("/")
+            .header(rocket::http::Header::new("Accept-Language", "en;q=0.5, de-AT, de;q=0.8"))
+            .dispatch()
+            .into_string()
+            .unwrap()
+    );
+
+    assert_eq!(
+        "fr",
+        client
+            .get("/")
+            .header(rocket::http::Header::new("Accept-Language", "de-AT"))
+            .cookie(Cookie::new(Locale::COOKIE_NAME, "fr"))
+            .dispatch()
+            .into_string()
+            .unwrap()
+    );
+}
+
+#[test]
+fn variant_names() {
+    let name = |name: &str, file_path: &str| {
+        template_variant_name(name, file_path).map(|name| name.into_owned())
+    };
+
+    assert_eq!(Some("terms.de"), name("terms", "tests/views/terms.de.hbs").as_deref());
+    assert_eq!(Some("legal/terms.de-AT"), name("legal/terms", "views/terms.de_at.hbs").as_deref());
+    assert_eq!(Some("terms"), name("terms", "tests/views/terms.hbs").as_deref());
+    assert_eq!(Some("index"), name("index", "tests/views/index.min.hbs").as_deref());
+    assert_eq!(Some("page"), name("page", "tests/views/page.old.hbs").as_deref());
+    assert_eq!(Some("about"), name("about", "tests/views/terms.de.hbs").as_deref());
+
+    // an explicit language tag overrides the file name
+    assert_eq!(Some("terms.de"), name("terms@de", "tests/views/terms.hbs").as_deref());
+    assert_eq!(Some("terms.yue"), name("terms@yue", "tests/views/terms.hbs").as_deref());
+    assert_eq!(None, name("terms@", "tests/views/terms.hbs"));
+    assert_eq!(None, name("terms@1x", "tests/views/terms.hbs"));
+    assert_eq!(None, name("@de", "tests/views/terms.de.hbs"));
+
+    let rocket = rocket::build().attach(HandlebarsResponse::fairing(|handlebars| {
+        handlebars_resources_initialize!(
+            handlebars,
+            "index" => "tests/views/index.min.hbs",
+            "page" => "tests/views/page.old.hbs",
+        );
+    }));
+
+    let client = Client::untracked(rocket).unwrap();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    assert_eq!("<p>index</p>\n", cm.render("index", json!({})));
+    assert_eq!("<p>old</p>\n", cm.render("page", json!({})));
+}
+
+#[test]
+fn template_variants() {
+    let rocket = rocket::build().attach(HandlebarsResponse::fairing(|handlebars| {
+        handlebars_resources_initialize!(
+            handlebars,
+            "terms" => "tests/views/terms.hbs",
+            "terms" => "tests/views/terms.de.hbs",
+            "terms" => "tests/views/terms.de-AT.hbs",
+        );
+    }));
+
+    let client = Client::untracked(rocket).unwrap();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    let resolve = |locale: Locale| cm.resolve_template(&locale, "terms").into_owned();
+
+    assert_eq!("terms.de-AT", resolve(Locale::new("de-AT")));
+    assert_eq!("terms.de", resolve(Locale::new("de-CH")));
+    assert_eq!("terms.de", resolve(Locale::from_tags(["fr", "de"])));
+    assert_eq!("terms", resolve(Locale::new("fr")));
+    assert_eq!("terms", resolve(Locale::default()));
+
+    assert_eq!("<h1>AGB</h1>\n", cm.render_localized(&Locale::new("de"), "terms", json!({})));
+    assert_eq!("<h1>Terms</h1>\n", cm.render("terms", json!({})));
+
+    #[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+    {
+        let build = |locale: Locale| {
+            handlebars_response_cache!(
+                localized cm,
+                EtagIfNoneMatch::default(),
+                locale,
+                "terms",
+                "terms"
+            )
+        };
+
+        build(Locale::new("de-AT"));
+        build(Locale::from_tags(["de-CH", "fr"]));
+        build(Locale::new("de-DE"));
+        build(Locale::new("fr"));
+
+        // the keys only vary among the variants
+        assert_eq!(vec!["terms", "terms@de", "terms@de-AT"], {
+            let mut keys: Vec<String> = cm.entries().into_iter().map(|info| info.key).collect();
+
+            keys.sort();
+
+            keys
+        });
+
+        assert_eq!("<h1>AGB (Österreich)</h1>", cm.get("terms@de-AT").unwrap().0.as_ref());
+        assert_eq!("<h1>AGB</h1>", cm.get("terms@de").unwrap().0.as_ref());
+        assert_eq!("<h1>Terms</h1>", cm.get("terms").unwrap().0.as_ref());
+
+        handlebars_response_cache!(
+            localized disable_minify cm,
+            EtagIfNoneMatch::default(),
+            Locale::new("de"),
+            "terms-full",
+            "terms"
+        );
+
+        assert_eq!("<h1>AGB</h1>\n", cm.get("terms-full@de").unwrap().0.as_ref());
+    }
+}
+
+#[cfg(any(not(debug_assertions), feature = "debug_cache"))]
+#[test]
+fn localized_fragments() {
+    let rocket = rocket::build().attach(HandlebarsResponse::fairing(|handlebars| {
+        handlebars
+            .register_template_string("page", "{{#cache \"greeting\"}}{{greeting}}{{/cache}}")
+            .unwrap();
+        handlebars
+            .register_template_string("page.de", "{{#cache \"greeting\"}}{{greeting}}{{/cache}}")
+            .unwrap();
+    }));
+
+    let client = Client::untracked(rocket).unwrap();
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    let render = |tag: &str, greeting: &str| {
+        cm.render_localized(&Locale::new(tag), "page", json!({ "greeting": greeting }))
+    };
+
+    assert_eq!("Hallo", render("de", "Hallo"));
+    assert_eq!("Hello", render("en", "Hello"));
+    assert_eq!("Hallo", render("de", "Servus"));
+    assert_eq!("Hallo", render("de-AT", "Servus"));
+    assert_eq!("Hello", render("fr", "Bonjour"));
+    assert!(cm.contains_key("fragment:greeting@de"));
+    assert!(cm.contains_key("fragment:greeting"));
+    assert_eq!(2, cm.entries().len());
+}
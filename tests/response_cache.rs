@@ -0,0 +1,160 @@
+use std::{sync::Arc, thread};
+
+use rocket_include_handlebars::*;
+
+fn entry(html: &str) -> CacheEntry {
+    CacheEntry::new(Arc::from(html), Arc::new(EntityTag::from_data(html)))
+}
+
+#[test]
+fn lru_eviction() {
+    let cache = LruResponseCache::new(2);
+
+    cache.insert(String::from("a"), entry("a"));
+    cache.insert(String::from("b"), entry("b"));
+
+    assert!(cache.get("a").is_some());
+
+    cache.insert(String::from("c"), entry("c"));
+
+    assert!(cache.contains_key("a"));
+    assert!(!cache.contains_key("b"));
+    assert!(cache.contains_key("c"));
+
+    let stats = cache.stats();
+
+    assert_eq!(1, stats.evictions);
+    assert_eq!(3, stats.insertions);
+    assert_eq!(2, stats.entries);
+}
+
+#[test]
+fn sharded() {
+    let cache = ShardedResponseCache::with_shards(2, 1);
+
+    cache.insert(String::from("a"), entry("a"));
+    cache.insert(String::from("b"), entry("b"));
+
+    assert!(cache.get("a").is_some());
+
+    cache.insert(String::from("c"), entry("c"));
+
+    assert_eq!(vec!["c", "a"], cache.entries().iter().map(|e| e.key.as_str()).collect::<Vec<_>>());
+    assert_eq!(1, cache.stats().evictions);
+
+    let cache = ShardedResponseCache::with_shards(64, 8);
+
+    cache.track_stats_prefix("blog/");
+
+    for i in 0..64 {
+        cache.insert(format!("blog/{}", i), entry("<p></p>"));
+    }
+
+    cache.insert(String::from("home"), entry("<p>home</p>"));
+
+    let stats = cache.stats_prefix("blog/").unwrap();
+
+    assert_eq!(64, stats.insertions);
+    assert_eq!(stats.entries, cache.remove_prefix("blog/"));
+    assert_eq!(vec!["home"], cache.entries().iter().map(|e| e.key.as_str()).collect::<Vec<_>>());
+
+    // more shards than the capacity are clamped, so that every key can be cached
+    let cache = ShardedResponseCache::with_shards(2, 8);
+
+    for i in 0..32 {
+        let key = i.to_string();
+
+        cache.insert(key.clone(), entry("<p></p>"));
+
+        assert!(cache.contains_key(&key));
+    }
+
+    assert_eq!(2, cache.stats().entries);
+}
+
+#[test]
+fn sharded_concurrency() {
+    const THREADS: usize = 8;
+    const KEYS: usize = 100;
+    const ROUNDS: usize = 20;
+
+    let cache = Arc::new(ShardedResponseCache::with_shards(256, 8));
+
+    let threads = (0..THREADS)
+        .map(|t| {
+            let cache = cache.clone();
+
+            thread::spawn(move || {
+                for _ in 0..ROUNDS {
+                    for i in 0..KEYS {
+                        let key = format!("{}/{}", t, i);
+
+                        match cache.get(&key) {
+                            Some(cached) => assert_eq!(key, cached.html.as_ref()),
+                            None => {
+                                // only this thread inserts its keys, so nothing is replaced
+                                assert!(cache.insert(key.clone(), entry(&key)).is_none());
+                            },
+                        }
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let stats = cache.stats();
+
+    assert_eq!((THREADS * KEYS * ROUNDS) as u64, stats.hits + stats.misses);
+    assert_eq!(stats.misses, stats.insertions);
+    assert!(stats.evictions > 0);
+    assert_eq!(stats.insertions - stats.evictions, stats.entries as u64);
+    assert!(stats.entries <= 256);
+
+    let entries = cache.entries();
+
+    assert_eq!(stats.entries, entries.len());
+
+    for info in entries {
+        assert_eq!(info.key, cache.peek(&info.key).unwrap().html.as_ref());
+    }
+}
+
+#[test]
+fn key_value_shared_store() {
+    let store = Arc::new(MemoryKeyValueStore::new());
+
+    let cache_1 = KeyValueResponseCache::new(store.clone());
+    let cache_2 = KeyValueResponseCache::new(store.clone());
+
+    cache_1.insert(String::from("blog/1"), entry("<p>1</p>"));
+    cache_1.insert(String::from("home"), entry("<p>home</p>"));
+
+    let cached = cache_2.get("blog/1").unwrap();
+
+    assert_eq!("<p>1</p>", cached.html.as_ref());
+    assert!(cached.etag.strong_eq(&EntityTag::from_data("<p>1</p>")));
+
+    assert_eq!(1, cache_2.stats().hits);
+
+    let expiring = entry("<p>2</p>").with_ttl(std::time::Duration::from_secs(300));
+
+    cache_1.insert(String::from("blog/2"), expiring.clone());
+
+    assert_eq!(expiring.expires_at, cache_2.peek("blog/2").unwrap().expires_at);
+    assert!(cached.expires_at.is_none());
+
+    cache_1.remove("blog/2");
+    assert_eq!(2, cache_2.stats().entries);
+
+    assert_eq!(1, cache_2.remove_prefix("blog/"));
+    assert!(cache_1.get("blog/1").is_none());
+
+    cache_2.clear();
+
+    assert!(cache_1.entries().is_empty());
+    assert!(store.keys("").is_empty());
+}
@@ -0,0 +1,96 @@
+use rocket::local::blocking::Client;
+use rocket_include_handlebars::*;
+use serde_json::json;
+
+#[rocket::get("/users/<id>")]
+fn user_profile(id: u64) -> String {
+    id.to_string()
+}
+
+#[rocket::get("/search?<q>&<page>&lang=en")]
+fn search(q: &str, page: Option<u32>) -> String {
+    format!("{} {:?}", q, page)
+}
+
+#[rocket::get("/files/<path..>")]
+fn files(path: std::path::PathBuf) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+#[rocket::get("/")]
+fn index() -> &'static str {
+    "index"
+}
+
+fn client(template: &str) -> Client {
+    let template = template.to_string();
+
+    let rocket = rocket::build()
+        .mount("/", rocket::routes![index, search, files])
+        .mount("/app", rocket::routes![user_profile])
+        .attach(HandlebarsResponse::fairing(move |handlebars| {
+            handlebars.register_template_string("page", &template).unwrap();
+        }));
+
+    Client::untracked(rocket).unwrap()
+}
+
+fn render(template: &str, context: serde_json::Value) -> String {
+    let client = client(template);
+
+    let cm = client.rocket().state::<HandlebarsContextManager>().unwrap();
+
+    cm.render("page", context)
+}
+
+#[test]
+fn path_params() {
+    assert_eq!("/app/users/42", render("{{url \"user_profile\" id=42}}", json!({})));
+    assert_eq!("/", render("{{url \"index\"}}", json!({})));
+
+    assert_eq!(
+        "/app/users/a%20b%2Fc",
+        render("{{url \"user_profile\" id=user.id}}", json!({ "user": { "id": "a b/c" } }))
+    );
+
+    assert_eq!(
+        "/files/css/site%20main.css",
+        render("{{url \"files\" path=\"/css/site main.css\"}}", json!({}))
+    );
+}
+
+#[test]
+fn query_params() {
+    assert_eq!(
+        "/search?q=rust%20%26%20rocket&page=2&lang=en",
+        render("{{{url \"search\" q=\"rust & rocket\" page=2}}}", json!({}))
+    );
+
+    assert_eq!("/search?q=a&lang=en", render("{{{url \"search\" q=\"a\"}}}", json!({})));
+
+    assert_eq!(
+        "/app/users/1?sort=asc&tab=posts",
+        render("{{{url \"user_profile\" id=1 tab=\"posts\" sort=\"asc\"}}}", json!({}))
+    );
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn errors() {
+    assert_eq!("#", render("{{url \"missing\"}}", json!({})));
+    assert_eq!("#", render("{{url \"user_profile\"}}", json!({})));
+}
+
+#[cfg(not(debug_assertions))]
+#[test]
+#[should_panic]
+fn unknown_route() {
+    render("{{url \"missing\"}}", json!({}));
+}
+
+#[cfg(not(debug_assertions))]
+#[test]
+#[should_panic]
+fn missing_param() {
+    render("{{url \"user_profile\"}}", json!({}));
+}